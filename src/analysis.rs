@@ -0,0 +1,319 @@
+//! This module contains the subcommands which inspect the languages of patterns
+//! instead of searching for them
+//!
+
+use std::io::Write;
+
+use grep_like_tool::Regex;
+
+use crate::pattern_options::{self, PatternOptions};
+
+/// the names of the subcommands, an invocation starting with one of them runs it
+pub const COMMANDS: [&str; 3] = ["equiv", "enumerate", "info"];
+
+/// length of the longest strings enumerated when no MAX_LEN is given
+const DEFAULT_MAX_LEN: usize = 5;
+
+/// The question asked about the patterns
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// do both patterns match the same strings
+    Equiv { first: String, second: String },
+    /// which strings of at most `max_len` characters does the pattern match
+    Enumerate { pattern: String, max_len: usize },
+    /// what are the shortest strings the pattern matches and does not match
+    Info { pattern: String },
+}
+
+/// A parsed subcommand invocation
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnalysisConfig {
+    pub command: Command,
+    pub options: PatternOptions,
+}
+
+/// parse the command line arguments (without the program name) of a subcommand
+pub fn parse_args(args: &[String]) -> Result<AnalysisConfig, String> {
+    let mut options = PatternOptions::default();
+
+    let (name, args) = args
+        .split_first()
+        .ok_or_else(|| "no subcommand given".to_string())?;
+
+    let positional = pattern_options::parse_flags(args, &mut options, |_| false)?;
+
+    let command = match name.as_str() {
+        "equiv" => match <[String; 2]>::try_from(positional) {
+            Ok([first, second]) => Command::Equiv { first, second },
+            Err(_) => return Err("equiv takes exactly two patterns".to_string()),
+        },
+        "enumerate" => {
+            let mut positional = positional.into_iter();
+            let pattern = positional
+                .next()
+                .ok_or_else(|| "no pattern given".to_string())?;
+            let max_len = match positional.next() {
+                Some(max_len) => max_len
+                    .parse()
+                    .map_err(|err| format!("invalid MAX_LEN {}: {}", max_len, err))?,
+                None => DEFAULT_MAX_LEN,
+            };
+
+            if positional.next().is_some() {
+                return Err("enumerate takes a pattern and at most one length".to_string());
+            }
+
+            Command::Enumerate { pattern, max_len }
+        }
+        "info" => match <[String; 1]>::try_from(positional) {
+            Ok([pattern]) => Command::Info { pattern },
+            Err(_) => return Err("info takes exactly one pattern".to_string()),
+        },
+        _ => return Err(format!("unknown subcommand {}", name)),
+    };
+
+    Ok(AnalysisConfig { command, options })
+}
+
+/// runs the subcommand and returns the exit code,
+/// 0 if the answer is yes, 1 if it is no and 2 on errors
+pub fn run<W: Write>(config: &AnalysisConfig, out: &mut W) -> i32 {
+    let result = match &config.command {
+        Command::Equiv { first, second } => run_equiv(first, second, &config.options, out),
+        Command::Enumerate { pattern, max_len } => {
+            run_enumerate(pattern, *max_len, &config.options, out)
+        }
+        Command::Info { pattern } => run_info(pattern, &config.options, out),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("grep-like-tool: {}", err);
+            2
+        }
+    }
+}
+
+/// the exit code of a subcommand, or the message of its error
+type AnalysisResult = Result<i32, String>;
+
+/// compiles a pattern, turning errors into a message with the offending part of it
+fn compile(pattern: &str, options: &PatternOptions) -> Result<Regex, String> {
+    pattern_options::regex_builder(pattern, options)
+        .build()
+        .map_err(|err| pattern_options::error_message(&err))
+}
+
+fn write_line<W: Write>(out: &mut W, line: &str) -> Result<(), String> {
+    writeln!(out, "{}", line).map_err(|err| err.to_string())
+}
+
+fn run_equiv<W: Write>(
+    first: &str,
+    second: &str,
+    options: &PatternOptions,
+    out: &mut W,
+) -> AnalysisResult {
+    let first = compile(first, options)?;
+    let second = compile(second, options)?;
+
+    match first.dfa().equivalent(second.dfa()) {
+        Ok(()) => {
+            write_line(out, "the patterns are equivalent")?;
+            Ok(0)
+        }
+        Err(witness) => {
+            let matched_by = if first.is_full_match(&witness) {
+                "first"
+            } else {
+                "second"
+            };
+            write_line(
+                out,
+                &format!(
+                    "the patterns are not equivalent, {:?} is matched only by the {} one",
+                    witness, matched_by
+                ),
+            )?;
+            Ok(1)
+        }
+    }
+}
+
+fn run_enumerate<W: Write>(
+    pattern: &str,
+    max_len: usize,
+    options: &PatternOptions,
+    out: &mut W,
+) -> AnalysisResult {
+    let regex = compile(pattern, options)?;
+    let mut any_printed = false;
+
+    for string in regex.dfa().enumerate(max_len) {
+        write_line(out, &string)?;
+        any_printed = true;
+    }
+
+    Ok(if any_printed { 0 } else { 1 })
+}
+
+fn run_info<W: Write>(pattern: &str, options: &PatternOptions, out: &mut W) -> AnalysisResult {
+    let regex = compile(pattern, options)?;
+    let dfa = regex.dfa();
+
+    match dfa.shortest_accepted() {
+        Some(string) if string.is_empty() => {
+            write_line(out, "the shortest match is the empty string")?
+        }
+        Some(string) => write_line(out, &format!("the shortest match is {:?}", string))?,
+        None => write_line(out, "the pattern matches no string at all")?,
+    }
+
+    match dfa.shortest_rejected() {
+        Some(string) if string.is_empty() => write_line(out, "it rejects even the empty string")?,
+        Some(string) => write_line(
+            out,
+            &format!("the shortest string it rejects is {:?}", string),
+        )?,
+        None => write_line(out, "it matches every string")?,
+    }
+
+    match dfa.language_size() {
+        Some(size) => write_line(out, &format!("it matches {} strings", size))?,
+        None => write_line(out, "it matches infinitely many strings")?,
+    }
+
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn run_command(list: &[&str]) -> (i32, String) {
+        let config = parse_args(&args(list)).unwrap();
+        let mut out = Vec::new();
+        let code = run(&config, &mut out);
+
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn check_parsing_arguments() {
+        let config = parse_args(&args(&["equiv", "-E", "a*", "--", "-a"])).unwrap();
+        assert_eq!(
+            config.command,
+            Command::Equiv {
+                first: "a*".to_string(),
+                second: "-a".to_string()
+            }
+        );
+        assert!(config.options.infix);
+        assert!(!config.options.ignore_case);
+
+        let config = parse_args(&args(&["enumerate", "-i", "symbol(a)", "3"])).unwrap();
+        assert_eq!(
+            config.command,
+            Command::Enumerate {
+                pattern: "symbol(a)".to_string(),
+                max_len: 3
+            }
+        );
+        assert!(config.options.ignore_case);
+
+        let config = parse_args(&args(&["enumerate", "symbol(a)"])).unwrap();
+        assert_eq!(
+            config.command,
+            Command::Enumerate {
+                pattern: "symbol(a)".to_string(),
+                max_len: DEFAULT_MAX_LEN
+            }
+        );
+
+        assert!(parse_args(&args(&["enumerate", "symbol(a)", "x"])).is_err());
+        assert!(parse_args(&args(&["enumerate", "symbol(a)", "1", "2"])).is_err());
+        let config = parse_args(&args(&["info", "--infix", "a"])).unwrap();
+        assert_eq!(
+            config.command,
+            Command::Info {
+                pattern: "a".to_string()
+            }
+        );
+        assert!(config.options.infix);
+
+        assert!(parse_args(&args(&["info"])).is_err());
+        assert!(parse_args(&args(&["equiv", "a"])).is_err());
+        assert!(parse_args(&args(&["equiv", "-x", "a", "b"])).is_err());
+        assert!(parse_args(&args(&["bogus", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn check_equiv() {
+        let (code, output) = run_command(&["equiv", "-E", "(a|b)*", "(a*b*)*"]);
+        assert_eq!(code, 0);
+        assert_eq!(output, "the patterns are equivalent\n");
+
+        let (code, output) = run_command(&["equiv", "-E", "a*", "a+"]);
+        assert_eq!(code, 1);
+        assert_eq!(
+            output,
+            "the patterns are not equivalent, \"\" is matched only by the first one\n"
+        );
+
+        let (code, output) = run_command(&["equiv", "-Ei", "ab", "[aA]b"]);
+        assert_eq!(code, 0, "{}", output);
+
+        let (code, output) = run_command(&["equiv", "-E", "ab", "[aA]b"]);
+        assert_eq!(code, 1);
+        assert!(output.contains("\"Ab\" is matched only by the second one"));
+
+        let (code, _) = run_command(&["equiv", "-E", "a", "(a"]);
+        assert_eq!(code, 2);
+    }
+
+    #[test]
+    fn check_enumerate() {
+        let (code, output) = run_command(&["enumerate", "-E", "(a|bc)*", "3"]);
+        assert_eq!(code, 0);
+        assert_eq!(output, "\na\naa\nbc\naaa\nabc\nbca\n");
+
+        let (code, output) = run_command(&["enumerate", "-Ei", "ab"]);
+        assert_eq!(code, 0);
+        assert_eq!(output, "AB\nAb\naB\nab\n");
+
+        let (code, output) = run_command(&["enumerate", "-E", "a{4}", "3"]);
+        assert_eq!(code, 1);
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn check_info() {
+        let (code, output) = run_command(&["info", "-E", "ab|c*"]);
+        assert_eq!(code, 0);
+        assert_eq!(
+            output,
+            "the shortest match is the empty string\n\
+             the shortest string it rejects is \"a\"\n\
+             it matches infinitely many strings\n"
+        );
+
+        let (_, output) = run_command(&["info", "-E", "a[bc]"]);
+        assert_eq!(
+            output,
+            "the shortest match is \"ab\"\n\
+             it rejects even the empty string\n\
+             it matches 2 strings\n"
+        );
+
+        let (_, output) = run_command(&["info", "-E", "[]"]);
+        assert!(output.starts_with("the pattern matches no string at all\n"));
+
+        let (_, output) = run_command(&["info", "-E", ".*"]);
+        assert!(output.contains("it matches every string\n"));
+    }
+}
@@ -0,0 +1,233 @@
+//! This module contains `BigCount`, an unbounded non-negative integer
+//! for counting the strings of a language
+//!
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    iter::Sum,
+    ops::{Add, AddAssign},
+};
+
+/// A non-negative integer without an upper bound
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigCount {
+    // base 2^32 digits, the least significant first, without leading zero digits
+    digits: Vec<u32>,
+}
+
+impl BigCount {
+    pub fn zero() -> BigCount {
+        BigCount { digits: Vec::new() }
+    }
+
+    pub fn one() -> BigCount {
+        BigCount::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// returns the value if it fits in a u128
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.digits.len() > 4 {
+            return None;
+        }
+
+        Some(
+            self.digits
+                .iter()
+                .rev()
+                .fold(0, |value, &digit| (value << 32) | digit as u128),
+        )
+    }
+
+    /// returns `self * factor`
+    pub fn mul_small(&self, factor: u32) -> BigCount {
+        let mut digits = Vec::with_capacity(self.digits.len() + 1);
+        let mut carry = 0u64;
+
+        for &digit in self.digits.iter() {
+            let product = digit as u64 * factor as u64 + carry;
+            digits.push(product as u32);
+            carry = product >> 32;
+        }
+        digits.push(carry as u32);
+
+        BigCount::from_digits(digits)
+    }
+
+    /// returns `self - other`, `None` if `other` is larger
+    pub fn checked_sub(&self, other: &BigCount) -> Option<BigCount> {
+        if *self < *other {
+            return None;
+        }
+
+        let mut digits = Vec::with_capacity(self.digits.len());
+        let mut borrow = 0i64;
+
+        for (index, &digit) in self.digits.iter().enumerate() {
+            let subtrahend = other.digits.get(index).copied().unwrap_or(0) as i64 + borrow;
+            let mut difference = digit as i64 - subtrahend;
+
+            borrow = 0;
+            if difference < 0 {
+                difference += 1 << 32;
+                borrow = 1;
+            }
+            digits.push(difference as u32);
+        }
+
+        Some(BigCount::from_digits(digits))
+    }
+
+    /// returns the quotient and remainder of the division by `divisor`, which must not be 0
+    pub fn div_rem_small(&self, divisor: u32) -> (BigCount, u32) {
+        let mut digits = vec![0; self.digits.len()];
+        let mut remainder = 0u64;
+
+        for (index, &digit) in self.digits.iter().enumerate().rev() {
+            let current = (remainder << 32) | digit as u64;
+            digits[index] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+
+        (BigCount::from_digits(digits), remainder as u32)
+    }
+
+    /// the base 2^32 digits, the least significant first
+    pub(crate) fn digits(&self) -> &[u32] {
+        &self.digits
+    }
+
+    pub(crate) fn from_digits(mut digits: Vec<u32>) -> BigCount {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        BigCount { digits }
+    }
+}
+
+impl From<u64> for BigCount {
+    fn from(value: u64) -> BigCount {
+        BigCount::from_digits(vec![value as u32, (value >> 32) as u32])
+    }
+}
+
+impl AddAssign<&BigCount> for BigCount {
+    fn add_assign(&mut self, other: &BigCount) {
+        let len = self.digits.len().max(other.digits.len());
+        self.digits.resize(len + 1, 0);
+        let mut carry = 0u64;
+
+        for index in 0..=len {
+            let sum = self.digits[index] as u64
+                + other.digits.get(index).copied().unwrap_or(0) as u64
+                + carry;
+            self.digits[index] = sum as u32;
+            carry = sum >> 32;
+        }
+
+        *self = BigCount::from_digits(std::mem::take(&mut self.digits));
+    }
+}
+
+impl Add for BigCount {
+    type Output = BigCount;
+
+    fn add(mut self, other: BigCount) -> BigCount {
+        self += &other;
+        self
+    }
+}
+
+impl Sum for BigCount {
+    fn sum<I: Iterator<Item = BigCount>>(iter: I) -> BigCount {
+        iter.fold(BigCount::zero(), Add::add)
+    }
+}
+
+impl Ord for BigCount {
+    fn cmp(&self, other: &BigCount) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.iter().rev().cmp(other.digits.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigCount {
+    fn partial_cmp(&self, other: &BigCount) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // groups of 9 decimal digits, the least significant first
+        let mut groups = Vec::new();
+        let mut value = self.clone();
+
+        while !value.is_zero() {
+            let (quotient, remainder) = value.div_rem_small(1_000_000_000);
+            groups.push(remainder);
+            value = quotient;
+        }
+
+        match groups.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for group in rest.iter().rev() {
+                    write!(f, "{:09}", group)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_arithmetic() {
+        let a = BigCount::from(u64::MAX);
+        let b = a.clone() + BigCount::one();
+        assert_eq!(b.to_u128(), Some(u64::MAX as u128 + 1));
+        assert_eq!(b.checked_sub(&a), Some(BigCount::one()));
+        assert_eq!(a.checked_sub(&b), None);
+        assert!(a < b);
+
+        let c = b.mul_small(1 << 31).mul_small(4);
+        assert_eq!(c.to_u128(), Some(1 << 97));
+        assert_eq!(c.div_rem_small(3).1, ((1u128 << 97) % 3) as u32);
+
+        let total: BigCount = (0..10).map(BigCount::from).sum();
+        assert_eq!(total, BigCount::from(45));
+        assert!(BigCount::zero().is_zero());
+        assert_eq!(BigCount::from(0), BigCount::zero());
+    }
+
+    #[test]
+    fn check_display() {
+        assert_eq!(BigCount::zero().to_string(), "0");
+        assert_eq!(BigCount::from(1_000_000_007).to_string(), "1000000007");
+
+        // 2^128 no longer fits in a u128
+        let power = BigCount::from(1 << 32)
+            .mul_small(1 << 16)
+            .mul_small(1 << 16);
+        let power = power
+            .mul_small(1 << 16)
+            .mul_small(1 << 16)
+            .mul_small(1 << 16);
+        let power = power.mul_small(1 << 16);
+        assert_eq!(power.to_u128(), None);
+        assert_eq!(power.to_string(), "340282366920938463463374607431768211456");
+    }
+}
@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-#[allow(dead_code)]
+#[allow(dead_code, clippy::enum_variant_names)]
 #[derive(Debug, Error)]
 pub enum DFAError {
     #[error("Invalid transition: {0}")]
@@ -26,4 +26,10 @@ pub enum AutomatonError {
 
     #[error("NFA Error {0}")]
     NFAError(NFAError),
+
+    #[error("Symbol table mismatch: {0}")]
+    SymbolTableMismatch(String),
+
+    #[error("more than {0} states are needed")]
+    SizeLimitExceeded(usize),
 }
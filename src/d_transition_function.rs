@@ -3,7 +3,7 @@ use std::{collections::HashMap, ops::Index};
 use crate::{
     custom_errors::{AutomatonError, DFAError},
     state::State,
-    symbol_table::Symbol,
+    symbol_table::{Symbol, SymbolTable},
     transition_function::BasicFunctionsForTransitions,
 };
 
@@ -22,6 +22,29 @@ impl DTransitionFunction {
     pub fn is_valid_transition(&self, state: &State, symbol: &Symbol) -> bool {
         self.contains_state(state) && self.f[state].contains_key(symbol)
     }
+
+    /// moves the transitions on the symbols of `from` to the symbols of `to`, which has to
+    /// refine `from`, every symbol moves like the symbol of `from` containing it
+    pub fn remap(&self, from: &SymbolTable, to: &SymbolTable) -> Result<Self, AutomatonError> {
+        let refinement = from.refinement(to).ok_or_else(|| {
+            AutomatonError::SymbolTableMismatch(
+                "the new symbol table does not refine the old one".to_string(),
+            )
+        })?;
+
+        let mut transition_function = DTransitionFunction::new();
+        for (&state, symbol_to_next_state_map) in self.f.iter() {
+            let new_transitions = transition_function.f.entry(state).or_default();
+
+            for (new_symbol, symbol) in refinement.iter() {
+                if let Some(&next_state) = symbol_to_next_state_map.get(symbol) {
+                    new_transitions.insert(*new_symbol, next_state);
+                }
+            }
+        }
+
+        Ok(transition_function)
+    }
 }
 
 impl Index<(&State, &Symbol)> for DTransitionFunction {
@@ -49,7 +72,7 @@ impl BasicFunctionsForTransitions for DTransitionFunction {
             )));
         }
 
-        let entry = self.f.entry(*state).or_insert(HashMap::new());
+        let entry = self.f.entry(*state).or_default();
 
         if entry.contains_key(symbol) {
             return Err(AutomatonError::DFAError(DFAError::InvalidTransition(
@@ -62,7 +85,7 @@ impl BasicFunctionsForTransitions for DTransitionFunction {
     }
 
     fn extend(&mut self, increment: usize) {
-        let mut on_states: Vec<State> = self.f.keys().map(|&val| val).collect();
+        let mut on_states: Vec<State> = self.f.keys().copied().collect();
 
         on_states.sort();
         // going through keys in decreasing order, in order to avoid overlapping issue
@@ -92,9 +115,8 @@ mod tests {
     fn check_normal_transition() {
         let mut dt = DTransitionFunction::new();
 
-        let _ = dt
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        dt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         assert!(dt.f.contains_key(&0));
         assert!(dt.f[&0].contains_key(&Symbol::Character('a')));
@@ -105,9 +127,8 @@ mod tests {
     fn check_multiple_transition() {
         let mut dt = DTransitionFunction::new();
 
-        let _ = dt
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        dt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let result = dt.add_transition(&0, &Symbol::Character('a'), &2);
         assert!(result.is_err_and(|err| err.to_string().contains("Adding more than one state")));
@@ -126,9 +147,8 @@ mod tests {
         let mut dt = DTransitionFunction::new();
 
         // (0, 'a') -> 1
-        let _ = dt
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        dt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         dt.extend(2);
         // (2, 'a') -> 3
@@ -138,4 +158,33 @@ mod tests {
         assert!(dt.f[&2].contains_key(&Symbol::Character('a')));
         assert_eq!(dt.f[&2][&Symbol::Character('a')], 3);
     }
+
+    #[test]
+    fn check_remapping_to_finer_symbol_table() {
+        let mut from = SymbolTable::new();
+        from.add_character('a');
+        let mut to = from.clone();
+        to.add_character('b');
+
+        let mut dt = DTransitionFunction::new();
+        dt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+        dt.add_transition(&0, &Symbol::Other, &2)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+
+        // b was one of the other characters before
+        let dt = dt.remap(&from, &to).unwrap();
+        assert_eq!(dt[(&0, &Symbol::Character('a'))], 1);
+        assert_eq!(dt[(&0, &Symbol::Character('b'))], 2);
+        assert_eq!(dt[(&0, &Symbol::Other)], 2);
+
+        // the other characters of a coarser table would include b
+        let result = dt.remap(&to, &from);
+        assert!(result.is_err_and(|err| err.to_string().contains("does not refine")));
+
+        let mut range = SymbolTable::new();
+        range.add_range('a', 'c');
+        let result = dt.remap(&to, &range);
+        assert!(result.is_err_and(|err| err.to_string().contains("does not refine")));
+    }
 }
@@ -1,10 +1,13 @@
 //! This module contains the necessary functions of DFA
 //!
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    convert::Infallible,
+};
 
 use crate::{
-    custom_errors::DFAError,
+    custom_errors::{AutomatonError, DFAError},
     disjoint_set_union::DSU,
     nfa::NFA,
     state::{State, StateSet},
@@ -58,9 +61,32 @@ impl DFA {
 
 #[allow(dead_code)]
 impl DFA {
+    /// create a DFA accepting no string at all, a single state without final states
+    pub fn empty(symbol_table: &SymbolTable) -> DFA {
+        let mut dfa = DFA {
+            num_states: 1,
+            symbol_table: symbol_table.clone(),
+            states: HashSet::from([0]),
+            begin_state_num: 0,
+            end_state_num: 0,
+            start_state: 0,
+            final_states: HashSet::new(),
+            transition_function: DTransitionFunction::new(),
+        };
+
+        for symbol in symbol_table.input_symbols() {
+            dfa.transition_function
+                .add_transition(&0, &symbol, &0)
+                .unwrap_or_else(|err| panic!("{}", err));
+        }
+
+        dfa
+    }
+
     /// create a DFA from a string
     pub fn from_string(s: &str, symbol_table: &SymbolTable) -> DFA {
-        let num_states = s.len() + 2;
+        let s_chars: Vec<char> = s.chars().collect();
+        let num_states = s_chars.len() + 2;
         let mut states = HashSet::new();
         let (begin_state_num, end_state_num) = (0, num_states - 1);
 
@@ -81,7 +107,7 @@ impl DFA {
             transition_function: DTransitionFunction::new(),
         };
 
-        if s.len() == 0 {
+        if s.is_empty() {
             dfa.final_states.insert(0);
 
             for &symbol in symbol_table.symbols() {
@@ -90,11 +116,11 @@ impl DFA {
                     _ => {
                         dfa.transition_function
                             .add_transition(&0, &symbol, &1)
-                            .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                            .unwrap_or_else(|err| panic!("{}", err));
 
                         dfa.transition_function
                             .add_transition(&1, &symbol, &1)
-                            .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                            .unwrap_or_else(|err| panic!("{}", err));
                     }
                 }
             }
@@ -102,26 +128,31 @@ impl DFA {
             return dfa;
         }
 
-        let s_bytes: Vec<_> = s.as_bytes().iter().map(|&val| val as char).collect();
-
-        let final_state = s.len();
-        let reject_state = s.len() + 1;
+        let final_state = s_chars.len();
+        let reject_state = s_chars.len() + 1;
 
         dfa.final_states.insert(final_state);
 
-        for state_num in 0..s_bytes.len() {
+        for (state_num, &expected) in s_chars.iter().enumerate() {
+            // a character inside a range moves on the symbol of that range,
+            // a character outside the symbol table is never matched
+            let expected = match symbol_table.lookup(expected) {
+                Symbol::Other => None,
+                symbol => Some(symbol),
+            };
+
             for &symbol in symbol_table.symbols() {
                 match symbol {
                     Symbol::Epsilon => continue,
-                    Symbol::Character(ch) if ch == s_bytes[state_num] => {
+                    _ if Some(symbol) == expected => {
                         dfa.transition_function
-                            .add_transition(&state_num, &Symbol::Character(ch), &(state_num + 1))
-                            .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                            .add_transition(&state_num, &symbol, &(state_num + 1))
+                            .unwrap_or_else(|err| panic!("{}", err));
                     }
-                    Symbol::Character(ch) => {
+                    _ => {
                         dfa.transition_function
-                            .add_transition(&state_num, &Symbol::Character(ch), &reject_state)
-                            .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                            .add_transition(&state_num, &symbol, &reject_state)
+                            .unwrap_or_else(|err| panic!("{}", err));
                     }
                 }
             }
@@ -130,13 +161,13 @@ impl DFA {
         for &symbol in symbol_table.symbols() {
             match symbol {
                 Symbol::Epsilon => continue,
-                Symbol::Character(ch) => {
+                _ => {
                     dfa.transition_function
-                        .add_transition(&final_state, &Symbol::Character(ch), &reject_state)
-                        .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                        .add_transition(&final_state, &symbol, &reject_state)
+                        .unwrap_or_else(|err| panic!("{}", err));
                     dfa.transition_function
-                        .add_transition(&reject_state, &Symbol::Character(ch), &reject_state)
-                        .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                        .add_transition(&reject_state, &symbol, &reject_state)
+                        .unwrap_or_else(|err| panic!("{}", err));
                 }
             }
         }
@@ -144,10 +175,12 @@ impl DFA {
         dfa
     }
 
+    /// to check if the DFA accepts `s`, characters outside the symbol table move on
+    /// `Symbol::Other` so they are rejected like any other unexpected character
     pub fn run(&self, s: &str) -> Result<bool, DFAError> {
         let mut current_state = self.start_state;
 
-        for symbol in s.as_bytes().iter().map(|&ch| Symbol::Character(ch as char)) {
+        for symbol in s.chars().map(|ch| self.symbol_table.lookup(ch)) {
             if !self.transition_function.contains_state(&current_state) {
                 return Err(DFAError::InvalidState("{current_state}".to_string()));
             }
@@ -195,103 +228,51 @@ impl DFA {
         // cleanup dfa before minimizing
         dfa.cleanup();
 
-        let n = dfa.num_states;
         let offset = dfa.begin_state_num;
-        let mut marked: Vec<Vec<bool>> = vec![vec![false; n]; n];
-
-        for first_state in dfa.begin_state_num..=dfa.end_state_num {
-            for second_state in first_state + 1..=dfa.end_state_num {
-                // first_state < second_state
-
-                if dfa.final_states.contains(&first_state)
-                    && !dfa.final_states.contains(&second_state)
-                {
-                    // first index always less than second index
-                    marked[first_state - offset][second_state - offset] = true;
-                } else if !dfa.final_states.contains(&first_state)
-                    && dfa.final_states.contains(&second_state)
-                {
-                    // first index always less than second index
-                    marked[first_state - offset][second_state - offset] = true;
-                }
-            }
-        }
-
-        loop {
-            let mut is_changed = false;
-
-            for first_state in dfa.begin_state_num..=dfa.end_state_num {
-                for second_state in first_state + 1..=dfa.end_state_num {
-                    if marked[first_state - offset][second_state - offset] {
-                        continue;
-                    }
-
-                    for symbol in dfa.symbol_table.symbols() {
-                        if *symbol == Symbol::Epsilon {
-                            // there will be no transition for epsilon symbol
-                            continue;
-                        }
-
-                        // if both have transition on the same symbol
-                        // and the pair (next_of_first_state, next_of_second_state) is marked
-                        // then mark this pair
-                        // since this is a DFA, it must have transition on same symbol
-                        let (next_of_first_state, next_of_second_state) = (
-                            dfa.transition_function[(&first_state, symbol)],
-                            dfa.transition_function[(&second_state, symbol)],
-                        );
-
-                        // since we are marking with the convention first_index < second_index
-                        let (next_of_first_state, next_of_second_state) = (
-                            next_of_first_state.min(next_of_second_state),
-                            next_of_first_state.max(next_of_second_state),
-                        );
-
-                        if marked[next_of_first_state - offset][next_of_second_state - offset]
-                            && !marked[first_state - offset][second_state - offset]
-                        {
-                            marked[first_state - offset][second_state - offset] = true;
-                            is_changed = true;
-                        }
-                    }
-                }
-            }
-
-            if !is_changed {
-                break;
-            }
-        }
+        let blocks = dfa.equivalence_classes();
 
         let mut dsu = DSU::new(dfa.num_states);
-        for first_state in dfa.begin_state_num..=dfa.end_state_num {
-            for second_state in first_state + 1..=dfa.end_state_num {
-                if !marked[first_state - offset][second_state - offset] {
-                    // then this pair is indistinguishable, i.e it can be merged
-                    dsu.union(first_state - offset, second_state - offset);
-                }
+        for block in blocks.iter() {
+            for &state in block.iter().skip(1) {
+                // the states of a block are indistinguishable, i.e they can be merged
+                dsu.union(block[0] - offset, state - offset);
             }
         }
 
         let state_representative_map = dsu.state_representative_map(offset);
 
-        let minimum_dfa_len = state_representative_map.len();
+        // number the merged states in the order of their representatives,
+        // so that the states of the minimum dfa are contiguous again
+        let mut representatives: Vec<State> = state_representative_map
+            .values()
+            .copied()
+            .collect::<HashSet<State>>()
+            .into_iter()
+            .collect();
+        representatives.sort();
+
+        let representative_to_new_state: HashMap<State, State> = representatives
+            .iter()
+            .enumerate()
+            .map(|(index, &representative)| (representative, offset + index))
+            .collect();
+        let new_state =
+            |state: &State| representative_to_new_state[&state_representative_map[state]];
+
+        let minimum_dfa_len = representatives.len();
         let mut new_dfa = DFA {
-            num_states: state_representative_map.len(),
+            num_states: minimum_dfa_len,
             symbol_table: dfa.symbol_table.clone(),
-            states: HashSet::from_iter(dfa.begin_state_num..dfa.begin_state_num + minimum_dfa_len),
-            begin_state_num: dfa.begin_state_num,
-            end_state_num: dfa.begin_state_num + minimum_dfa_len - 1,
-            start_state: state_representative_map[&dfa.begin_state_num],
-            final_states: HashSet::from_iter(
-                dfa.final_states
-                    .iter()
-                    .map(|&state| state_representative_map[&state]),
-            ),
+            states: HashSet::from_iter(offset..offset + minimum_dfa_len),
+            begin_state_num: offset,
+            end_state_num: offset + minimum_dfa_len - 1,
+            start_state: new_state(&dfa.start_state),
+            final_states: HashSet::from_iter(dfa.final_states.iter().map(new_state)),
             transition_function: DTransitionFunction::new(),
         };
 
-        for (curr_state, symbol_to_next_state_map) in dfa.transition_function.f {
-            if state_representative_map[&curr_state] != curr_state {
+        for (curr_state, symbol_to_next_state_map) in dfa.transition_function.f.iter() {
+            if state_representative_map[curr_state] != *curr_state {
                 // this is not present in minimum dfa
                 continue;
             }
@@ -300,18 +281,125 @@ impl DFA {
                 // curr_state is present in minimum dfa
                 new_dfa
                     .transition_function
-                    .add_transition(&curr_state, &symbol, &state_representative_map[&next_state])
-                    .unwrap_or_else(|err| panic!("{}", format!("{}", err.to_string())));
+                    .add_transition(&new_state(curr_state), symbol, &new_state(next_state))
+                    .unwrap_or_else(|err| panic!("{}", err));
             }
         }
 
         new_dfa
     }
 
+    /// partitions the states into the classes of indistinguishable states with
+    /// Hopcroft's algorithm, which refines the partition into final and other states
+    /// in O(n k log n) for n states and k symbols, each class is sorted
+    fn equivalence_classes(&self) -> Vec<Vec<State>> {
+        let offset = self.begin_state_num;
+        let n = self.num_states;
+        let symbols: Vec<&Symbol> = self
+            .symbol_table
+            .symbols()
+            .filter(|symbol| **symbol != Symbol::Epsilon)
+            .collect();
+
+        // predecessors[symbol][state] are the states moving to `state` on `symbol`
+        let mut predecessors: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new(); n]; symbols.len()];
+        for state in 0..n {
+            for (index, symbol) in symbols.iter().enumerate() {
+                if let Some(next_state) = self.get_transition(&(state + offset), symbol) {
+                    predecessors[index][next_state - offset].push(state);
+                }
+            }
+        }
+
+        let (final_states, other_states): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|state| self.final_states.contains(&(state + offset)));
+        let mut blocks: Vec<Vec<usize>> = [final_states, other_states]
+            .into_iter()
+            .filter(|block| !block.is_empty())
+            .collect();
+
+        let mut block_of = vec![0; n];
+        for (block_index, block) in blocks.iter().enumerate() {
+            for &state in block {
+                block_of[state] = block_index;
+            }
+        }
+
+        // the pairs of a block and a symbol still to split the other blocks with
+        let mut pending: Vec<(usize, usize)> = (0..blocks.len())
+            .flat_map(|block_index| (0..symbols.len()).map(move |index| (block_index, index)))
+            .collect();
+        let mut is_pending: HashSet<(usize, usize)> = pending.iter().copied().collect();
+
+        let mut moves_into_splitter = vec![false; n];
+
+        while let Some((splitter, symbol_index)) = pending.pop() {
+            is_pending.remove(&(splitter, symbol_index));
+
+            // the states moving into the splitter, grouped by their block
+            let mut touched: HashMap<usize, Vec<usize>> = HashMap::new();
+            for &state in blocks[splitter].iter() {
+                for &previous in predecessors[symbol_index][state].iter() {
+                    touched
+                        .entry(block_of[previous])
+                        .or_default()
+                        .push(previous);
+                }
+            }
+
+            for (block_index, moved) in touched {
+                if moved.len() == blocks[block_index].len() {
+                    continue;
+                }
+
+                // the states moving into the splitter become a block of their own
+                for &state in moved.iter() {
+                    moves_into_splitter[state] = true;
+                }
+                blocks[block_index].retain(|&state| !moves_into_splitter[state]);
+                for &state in moved.iter() {
+                    moves_into_splitter[state] = false;
+                }
+
+                let new_block = blocks.len();
+                for &state in moved.iter() {
+                    block_of[state] = new_block;
+                }
+                blocks.push(moved);
+
+                // it is enough to split with the smaller half, unless the block
+                // itself is still waiting to be used
+                for symbol_index in 0..symbols.len() {
+                    let block = if is_pending.contains(&(block_index, symbol_index))
+                        || blocks[new_block].len() <= blocks[block_index].len()
+                    {
+                        new_block
+                    } else {
+                        block_index
+                    };
+
+                    if is_pending.insert((block, symbol_index)) {
+                        pending.push((block, symbol_index));
+                    }
+                }
+            }
+        }
+
+        for block in blocks.iter_mut() {
+            for state in block.iter_mut() {
+                *state += offset;
+            }
+            block.sort();
+        }
+        blocks.sort();
+
+        blocks
+    }
+
     /// cleanup the dfa by removing inaccessible states and ordering the states
     pub fn cleanup(&mut self) {
         let mut q: VecDeque<State> = VecDeque::new();
-        q.push_back(0);
+        q.push_back(self.start_state);
         let mut visited: HashSet<State> = HashSet::new();
 
         // find the reachable states using BFS
@@ -365,16 +453,14 @@ impl DFA {
                         &symbol,
                         &existing_state_to_new_state_map[&next_state],
                     )
-                    .unwrap_or_else(|err| {
-                        panic!("Error in adding transition : {}", err.to_string())
-                    });
+                    .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
             }
         }
 
         // symbol table remains unchanged
         let num_states = visited.len();
         self.num_states = num_states;
-        self.states = HashSet::from_iter((0..num_states).into_iter());
+        self.states = HashSet::from_iter(0..num_states);
 
         self.begin_state_num = 0;
         self.end_state_num = num_states - 1;
@@ -423,6 +509,31 @@ impl DFA {
 
     /// converting NFA to a minimized DFA
     pub fn convert_to_dfa(nfa: NFA) -> DFA {
+        // nothing stops the construction, the error type has no values
+        let Ok(dfa) = DFA::subset_construction(nfa, |_| Ok::<(), Infallible>(()));
+
+        dfa
+    }
+
+    /// converting NFA to a minimized DFA, giving up with `None` as soon as
+    /// the subset construction reaches more than `state_limit` states
+    pub fn try_convert_to_dfa(nfa: NFA, state_limit: usize) -> Option<DFA> {
+        DFA::subset_construction(nfa, |num_states| {
+            if num_states > state_limit {
+                Err(())
+            } else {
+                Ok(())
+            }
+        })
+        .ok()
+    }
+
+    /// the subset construction followed by minimization, `check_size` is called with
+    /// the number of states found so far and stops the construction with its error
+    fn subset_construction<E>(
+        nfa: NFA,
+        mut check_size: impl FnMut(usize) -> Result<(), E>,
+    ) -> Result<DFA, E> {
         let mut curr_state_num = 0;
         let mut subset_to_num_map: HashMap<StateSet, State> = HashMap::new();
         let mut num_to_subset_map: HashMap<State, StateSet> = HashMap::new();
@@ -444,7 +555,9 @@ impl DFA {
         q.push_back(start_state_closure);
 
         let mut dfa = DFA {
-            num_states: 2_u32.pow(nfa.num_states() as u32) as usize,
+            // filled in once the reachable subsets are known, the full powerset
+            // of NFA states overflows for anything beyond a handful of symbols
+            num_states: 0,
             symbol_table: nfa.symbol_table().clone(),
             states: HashSet::new(),
             begin_state_num: 0,
@@ -461,6 +574,7 @@ impl DFA {
                 continue;
             }
             visited.insert(get_state_equivalent_number(curr_set_of_states.clone()));
+            check_size(visited.len())?;
 
             let curr_state_number = get_state_equivalent_number(curr_set_of_states.clone());
             dfa.states.insert(curr_state_number);
@@ -512,14 +626,23 @@ impl DFA {
         dfa.states = visited;
 
         // minimize the dfa
-        let dfa = dfa.minimized_dfa();
-
-        dfa
+        Ok(dfa.minimized_dfa())
     }
 }
 
 #[allow(dead_code)]
 impl DFA {
+    /// function for the DFA accepting the reversal of the language
+    pub fn reverse(&self) -> DFA {
+        DFA::convert_to_dfa(NFA::reverse_of_dfa(self))
+    }
+
+    /// the DFA accepting the reversal of the language, `None` if the
+    /// subset construction needs more than `state_limit` states
+    pub fn try_reverse(&self, state_limit: usize) -> Option<DFA> {
+        DFA::try_convert_to_dfa(NFA::reverse_of_dfa(self), state_limit)
+    }
+
     /// function for complement of a DFAs
     pub fn complement(&self) -> DFA {
         let mut dfa = self.clone();
@@ -531,89 +654,150 @@ impl DFA {
             }
         }
 
-        let dfa = dfa.minimized_dfa();
-        dfa
+        dfa.minimized_dfa()
     }
 
     /// function for intersection of 2 DFAs
-    pub fn intersection(&self, other: DFA) -> DFA {
+    pub fn intersection(&self, other: DFA) -> Result<DFA, AutomatonError> {
+        self.product(&other, BoolOp::And)
+    }
+
+    /// returns the DFA accepting the strings for which `op` holds on whether
+    /// `self` and `other` accept them, the symbol tables are merged if they differ
+    pub fn product(&self, other: &DFA, op: BoolOp) -> Result<DFA, AutomatonError> {
         if self.symbol_table != other.symbol_table {
-            panic!("Symbol table of 2 NFAs are not the same");
+            let symbol_table = self.symbol_table.merge(&other.symbol_table);
+
+            return self
+                .with_symbol_table(&symbol_table)?
+                .product(&other.with_symbol_table(&symbol_table)?, op);
         }
-        let x = self.num_states();
-        let y = other.num_states();
+
+        // a missing transition leads to a dead state, which is written as None
+        let start_pair = (Some(self.start_state), Some(other.start_state));
+        let mut pair_to_state_number: HashMap<(Option<State>, Option<State>), State> =
+            HashMap::from([(start_pair, 0)]);
+
+        let mut q: VecDeque<(Option<State>, Option<State>)> = VecDeque::new();
+        q.push_back(start_pair);
 
         let mut dfa = DFA {
-            num_states: x * y,
+            // filled in once the reachable pairs are known
+            num_states: 0,
             symbol_table: self.symbol_table.clone(),
             states: HashSet::new(),
             begin_state_num: 0,
-            end_state_num: x * y - 1,
+            end_state_num: 0,
             start_state: 0,
             final_states: HashSet::new(),
             transition_function: DTransitionFunction::new(),
         };
 
-        let mut curr_state_num: State = 0;
-        let mut pair_to_state_number: HashMap<(State, State), State> = HashMap::new();
+        let is_final =
+            |dfa: &DFA, state: Option<State>| state.is_some_and(|s| dfa.final_states.contains(&s));
 
-        pair_to_state_number.insert((self.start_state(), other.start_state()), curr_state_num);
-        curr_state_num += 1;
+        while let Some(pair) = q.pop_front() {
+            let state = pair_to_state_number[&pair];
 
-        for first_state in self.begin_state_num()..=self.end_state_num() {
-            for second_state in other.begin_state_num()..=other.end_state_num() {
-                let pair = (first_state, second_state);
+            if op.apply(is_final(self, pair.0), is_final(other, pair.1)) {
+                dfa.final_states.insert(state);
+            }
 
-                if !pair_to_state_number.contains_key(&pair) {
-                    pair_to_state_number.insert(pair, curr_state_num);
-                    curr_state_num += 1;
-                }
+            for symbol in self.symbol_table.input_symbols() {
+                let next_pair = (
+                    pair.0.and_then(|s| self.get_transition(&s, &symbol)),
+                    pair.1.and_then(|s| other.get_transition(&s, &symbol)),
+                );
 
-                if self.final_states().contains(&first_state)
-                    && other.final_states().contains(&second_state)
-                {
-                    // if both are in final states of respective machines, add that to final state
-                    // of the resultant dfa
-                    dfa.final_states.insert(pair_to_state_number[&pair]);
-                }
+                let next_state_number = pair_to_state_number.len();
+                let next_state = match pair_to_state_number.entry(next_pair) {
+                    Entry::Occupied(entry) => *entry.get(),
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_state_number);
+                        q.push_back(next_pair);
+                        next_state_number
+                    }
+                };
+
+                dfa.transition_function
+                    .add_transition(&state, &symbol, &next_state)
+                    .unwrap_or_else(|err| panic!("{}", err));
             }
         }
 
-        for first_state in self.begin_state_num()..=self.end_state_num() {
-            for second_state in other.begin_state_num()..=other.end_state_num() {
-                let pair = (first_state, second_state);
-                let state = pair_to_state_number[&pair];
+        let num_states = pair_to_state_number.len();
+        dfa.num_states = num_states;
+        dfa.states = HashSet::from_iter(0..num_states);
+        dfa.end_state_num = num_states - 1;
 
-                for symbol in dfa.symbol_table.symbols() {
-                    if *symbol == Symbol::Epsilon {
-                        continue;
-                    }
+        Ok(dfa.minimized_dfa())
+    }
 
-                    // transition will be always valid since it is a DFA
-                    // but there was a test case in which this was invalid
-                    // rectified it with check if it is none
-                    // but need to check it once
-                    let next_state_pair = (
-                        self.get_transition(&first_state, symbol),
-                        other.get_transition(&second_state, symbol),
-                    );
-
-                    if next_state_pair.0.is_none() || next_state_pair.1.is_none() {
-                        continue;
-                    }
-                    let next_state_pair = (next_state_pair.0.unwrap(), next_state_pair.1.unwrap());
+    /// returns the same DFA over `symbol_table`, which has to refine its own symbol table,
+    /// every symbol moves like the symbol of the old table containing it
+    pub fn with_symbol_table(&self, symbol_table: &SymbolTable) -> Result<DFA, AutomatonError> {
+        let mut dfa = self.clone();
+        dfa.transition_function = self
+            .transition_function
+            .remap(&self.symbol_table, symbol_table)?;
+        dfa.symbol_table = symbol_table.clone();
 
-                    let next_state = pair_to_state_number[&next_state_pair];
+        // symbols which had no transition before lead to a new dead state
+        let dead_state = self.end_state_num + 1;
+        let mut is_dead_state_used = false;
 
-                    let _ = dfa
-                        .transition_function
-                        .add_transition(&state, symbol, &next_state);
+        for state in self.states.iter() {
+            for symbol in symbol_table.input_symbols() {
+                if !dfa.transition_function.is_valid_transition(state, &symbol) {
+                    dfa.transition_function
+                        .add_transition(state, &symbol, &dead_state)
+                        .unwrap_or_else(|err| panic!("{}", err));
+                    is_dead_state_used = true;
                 }
             }
         }
 
-        let dfa = dfa.minimized_dfa();
-        dfa
+        if is_dead_state_used {
+            for symbol in symbol_table.input_symbols() {
+                dfa.transition_function
+                    .add_transition(&dead_state, &symbol, &dead_state)
+                    .unwrap_or_else(|err| panic!("{}", err));
+            }
+
+            dfa.states.insert(dead_state);
+            dfa.num_states += 1;
+            dfa.end_state_num = dead_state;
+        }
+
+        Ok(dfa)
+    }
+}
+
+/// Boolean operation deciding the final states of `DFA::product`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoolOp {
+    /// strings accepted by both DFAs
+    And,
+    /// strings accepted by either DFA
+    Or,
+    /// strings accepted by the first DFA but not by the second
+    Difference,
+    /// strings accepted by exactly one of the DFAs
+    Xor,
+    /// strings accepted by the second DFA or rejected by the first
+    Implies,
+}
+
+impl BoolOp {
+    /// whether a string is accepted by the product, given whether each DFA accepts it
+    pub fn apply(self, first: bool, second: bool) -> bool {
+        match self {
+            BoolOp::And => first && second,
+            BoolOp::Or => first || second,
+            BoolOp::Difference => first && !second,
+            BoolOp::Xor => first != second,
+            BoolOp::Implies => !first || second,
+        }
     }
 }
 
@@ -636,6 +820,30 @@ mod tests {
 
         let result = dfa.run("abd");
         assert!(result.is_ok_and(|res| !res));
+
+        // multi-byte characters are single symbols
+        symbol_table.add_character('é');
+        let dfa = DFA::from_string("éa", &symbol_table);
+        assert_eq!(dfa.num_states(), 4);
+        assert!(dfa.run("éa").is_ok_and(|res| res));
+        assert!(dfa.run("ea").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_acceptance_of_dfa_constructed_from_string_over_ranges() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_range('0', '9');
+        symbol_table.add_range('a', 'z');
+        symbol_table.add_character('x');
+
+        let dfa = DFA::from_string("4x2", &symbol_table);
+        assert!(dfa.run("4x2").is_ok_and(|res| res));
+
+        // the DFA only sees symbols, so the other digits of the range match as well
+        assert!(dfa.run("7x0").is_ok_and(|res| res));
+        assert!(dfa.run("4y2").is_ok_and(|res| !res));
+        assert!(dfa.run("4x").is_ok_and(|res| !res));
+        assert!(dfa.run("4x!").is_ok_and(|res| !res));
     }
 
     #[test]
@@ -656,7 +864,7 @@ mod tests {
     }
 
     #[test]
-    fn check_invalid_transition() {
+    fn check_rejection_outside_alphabet() {
         let mut symbol_table = SymbolTable::new();
         symbol_table.add_character('a');
         symbol_table.add_character('b');
@@ -664,7 +872,15 @@ mod tests {
         let dfa = DFA::from_string("abc", &symbol_table);
 
         let result = dfa.run("abc");
-        assert!(result.is_err_and(|res| res.to_string().contains("Invalid Transition")));
+        assert!(result.is_ok_and(|res| !res));
+
+        let dfa = DFA::from_string("ab", &symbol_table);
+
+        let result = dfa.run("abx");
+        assert!(result.is_ok_and(|res| !res));
+
+        let result = dfa.run("xab");
+        assert!(result.is_ok_and(|res| !res));
     }
 
     #[test]
@@ -740,6 +956,44 @@ mod tests {
         assert!(result.is_ok_and(|res| res));
     }
 
+    #[test]
+    fn check_conversion_of_large_nfa() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+
+        // 40 NFA states, far more subsets than fit in a u32
+        let mut nfa = NFA::from_symbol(&Symbol::Character('a'), &symbol_table);
+        for _ in 1..20 {
+            nfa = nfa
+                .concat(NFA::from_symbol(&Symbol::Character('a'), &symbol_table))
+                .unwrap();
+        }
+        let dfa = DFA::convert_to_dfa(nfa);
+
+        assert!(dfa.run(&"a".repeat(20)).is_ok_and(|res| res));
+        assert!(dfa.run(&"a".repeat(19)).is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_state_limit_of_conversion() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        symbol_table.add_character('b');
+
+        // the subset construction of `ab` needs the states for "", "a", "ab" and a dead state
+        let nfa = || {
+            NFA::from_symbol(&Symbol::Character('a'), &symbol_table)
+                .concat(NFA::from_symbol(&Symbol::Character('b'), &symbol_table))
+                .unwrap()
+        };
+
+        assert!(DFA::try_convert_to_dfa(nfa(), 3).is_none());
+
+        let dfa = DFA::try_convert_to_dfa(nfa(), 4).unwrap();
+        assert!(dfa.run("ab").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+    }
+
     #[test]
     fn check_complementation_of_dfa() {
         let mut symbol_table = SymbolTable::new();
@@ -756,6 +1010,182 @@ mod tests {
 
         let result = dfa.run("abd");
         assert!(result.is_ok_and(|res| res));
+
+        // the complement is taken over every character, not only the symbol table
+        let result = dfa.run("abx");
+        assert!(result.is_ok_and(|res| res));
+
+        let result = dfa.run("é");
+        assert!(result.is_ok_and(|res| res));
+    }
+
+    #[test]
+    fn check_empty_language() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        symbol_table.add_character('b');
+
+        let dfa = DFA::empty(&symbol_table);
+        for input in ["", "a", "ab", "x"] {
+            assert!(dfa.run(input).is_ok_and(|res| !res), "{}", input);
+        }
+
+        // no final states survive minimization either
+        let dfa = dfa.minimized_dfa();
+        assert!(dfa.final_states().is_empty());
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+
+        // its complement is every string
+        let complement = DFA::empty(&symbol_table).complement();
+        for input in ["", "a", "ab", "x"] {
+            assert!(complement.run(input).is_ok_and(|res| res), "{}", input);
+        }
+
+        let dfa = DFA::from_string("ab", &symbol_table)
+            .intersection(DFA::empty(&symbol_table))
+            .unwrap();
+        assert!(dfa.run("ab").is_ok_and(|res| !res));
+
+        let dfa = DFA::from_string("ab", &symbol_table)
+            .intersection(complement)
+            .unwrap();
+        assert!(dfa.run("ab").is_ok_and(|res| res));
+    }
+
+    #[test]
+    fn check_minimization_removes_merged_states() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+
+        // a* built as the union of "" and a a*
+        let a = || NFA::from_symbol(&Symbol::Character('a'), &symbol_table);
+        let a_plus = a().concat(a().kleene_star()).unwrap();
+        let nfa = NFA::from_symbol(&Symbol::Epsilon, &symbol_table)
+            .union(a_plus)
+            .unwrap();
+        let dfa = DFA::convert_to_dfa(nfa);
+
+        // one state for the strings of a and a dead state for the rest
+        assert_eq!(dfa.num_states(), 2);
+        assert_eq!(dfa.states().len(), 2);
+        for state in dfa.states() {
+            assert!(dfa.transition_function.contains_state(state));
+        }
+        assert!(dfa.run("aaa").is_ok_and(|res| res));
+        assert!(dfa.run("ab").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_minimization_of_large_dfa() {
+        // the strings whose eighth last character is an a, which needs a state for every
+        // choice of the last eight characters, besides the dead state for the others
+        let ast = crate::infix_parsing::parse_infix_reg_ex("(a|b)*a(a|b){7}").unwrap();
+        let dfa = DFA::convert_to_dfa(ast.to_nfa());
+        assert_eq!(dfa.num_states(), 257);
+
+        // minimizing a minimum dfa changes nothing
+        let dfa = dfa.minimized_dfa();
+        assert_eq!(dfa.num_states(), 257);
+        assert!(dfa.run("abbbbbbb").is_ok_and(|res| res));
+        assert!(dfa.run("abbbbbbbb").is_ok_and(|res| !res));
+        assert!(dfa.run("abbbbbbbc").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_minimization_after_extending() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        symbol_table.add_character('b');
+
+        // the start state is no longer state 0
+        let mut dfa = DFA::from_string("ab", &symbol_table);
+        dfa.extend(3);
+        let dfa = dfa.minimized_dfa();
+
+        assert_eq!(dfa.num_states(), 4);
+        assert!(dfa.run("ab").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+        assert!(dfa.run("abb").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_product_of_dfas() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        symbol_table.add_character('b');
+
+        let ab = DFA::from_string("ab", &symbol_table);
+        let empty_string = DFA::from_string("", &symbol_table);
+        let not_ab = ab.complement();
+
+        for (op, expected) in [
+            (BoolOp::And, [false, false, false]),
+            (BoolOp::Or, [true, true, false]),
+            (BoolOp::Difference, [true, false, false]),
+            (BoolOp::Xor, [true, true, false]),
+            (BoolOp::Implies, [false, true, true]),
+        ] {
+            let dfa = ab.product(&empty_string, op).unwrap();
+            for (input, expected) in ["ab", "", "a"].into_iter().zip(expected) {
+                assert_eq!(dfa.run(input).unwrap(), expected, "{:?} on {:?}", op, input);
+            }
+        }
+
+        // a string and its complement
+        let dfa = ab.product(&not_ab, BoolOp::Xor).unwrap();
+        assert!(dfa.final_states().len() == dfa.num_states());
+        let dfa = ab.product(&not_ab, BoolOp::And).unwrap();
+        assert!(dfa.final_states().is_empty());
+    }
+
+    #[test]
+    fn check_product_merging_symbol_tables() {
+        let mut first_table = SymbolTable::new();
+        first_table.add_range('a', 'z');
+        first_table.add_character('x');
+        let mut second_table = SymbolTable::new();
+        second_table.add_character('q');
+        second_table.add_character('0');
+
+        // the tables split the letters differently
+        let q = DFA::from_string("q", &second_table);
+        let x = DFA::from_string("x", &first_table);
+
+        let dfa = q.product(&x, BoolOp::Or).unwrap();
+        for (input, expected) in [("q", true), ("x", true), ("a", false), ("0", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        // every string other than x and q
+        let dfa = x.complement().product(&q, BoolOp::Difference).unwrap();
+        for (input, expected) in [("q", false), ("x", false), ("m", true), ("0", true)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn check_moving_to_finer_symbol_table() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        let mut finer_table = symbol_table.clone();
+        finer_table.add_range('a', 'c');
+
+        // a transition on a only, the symbols of b and c lead to a dead state
+        let mut dfa = DFA::from_string("a", &symbol_table);
+        dfa.transition_function
+            .f
+            .get_mut(&0)
+            .unwrap()
+            .remove(&Symbol::Other);
+
+        let dfa = dfa.with_symbol_table(&finer_table).unwrap();
+        assert_eq!(dfa.num_states(), 4);
+        assert!(dfa.run("a").is_ok_and(|res| res));
+        assert!(dfa.run("b").is_ok_and(|res| !res));
+        assert!(dfa.run("bc").is_ok_and(|res| !res));
+
+        let result = dfa.with_symbol_table(&symbol_table);
+        assert!(result.is_err_and(|err| err.to_string().contains("does not refine")));
     }
 
     #[test]
@@ -768,7 +1198,7 @@ mod tests {
 
         let dfa1 = DFA::from_string("a", &symbol_table);
         let dfa2 = DFA::from_string("a", &symbol_table);
-        let dfa = dfa1.intersection(dfa2);
+        let dfa = dfa1.intersection(dfa2).unwrap();
 
         let result = dfa.run("a");
         assert!(result.is_ok_and(|res| res));
@@ -778,7 +1208,7 @@ mod tests {
 
         let dfa1 = DFA::from_string("a", &symbol_table);
         let dfa2 = DFA::from_string("", &symbol_table);
-        let dfa = dfa1.intersection(dfa2);
+        let dfa = dfa1.intersection(dfa2).unwrap();
 
         let result = dfa.run("a");
         assert!(result.is_ok_and(|res| !res));
@@ -28,12 +28,13 @@ impl DSU {
         distinct_set_representatives.len()
     }
 
-    /// function to return a map of state to its parent
-    pub fn state_representative_map(&self, offset: usize) -> HashMap<State, State> {
+    /// function to return a map of state to the representative of its set
+    pub fn state_representative_map(&mut self, offset: usize) -> HashMap<State, State> {
         let mut map = HashMap::new();
 
-        for (state, &representative) in self.parent.iter().enumerate() {
-            map.insert(state + offset, representative + offset);
+        // the parent of a state need not be the representative until the path is compressed
+        for state in 0..self.parent.len() {
+            map.insert(state + offset, self.find_representative(state) + offset);
         }
 
         map
@@ -48,7 +49,7 @@ impl DSU {
         let par = self.find_representative(self.parent[state]);
         self.parent[state] = par;
 
-        return par;
+        par
     }
 
     /// function to unite 2 disjoint sets
@@ -68,3 +69,23 @@ impl DSU {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_state_representative_map() {
+        let mut dsu = DSU::new(4);
+
+        // 2 points to 1 until its path is compressed
+        dsu.union(1, 2);
+        dsu.union(0, 1);
+
+        let map = dsu.state_representative_map(5);
+        assert_eq!(map[&5], 5);
+        assert_eq!(map[&6], 5);
+        assert_eq!(map[&7], 5);
+        assert_eq!(map[&8], 8);
+    }
+}
@@ -0,0 +1,344 @@
+//! This module contains the line oriented search mode of the tool
+//!
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+};
+
+use grep_like_tool::{Regex, RegexError};
+
+use crate::pattern_options::{self, PatternOptions};
+
+/// name used when reporting matches read from stdin
+const STDIN_NAME: &str = "(standard input)";
+
+/// Options controlling what is printed for the selected lines
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GrepOptions {
+    /// -c, print only the number of selected lines per file
+    pub count: bool,
+    /// -v, select the lines which do not contain a match
+    pub invert: bool,
+    /// -l, print only the names of files with a selected line
+    pub files_with_matches: bool,
+    /// -n, prefix each line with its 1-based line number
+    pub line_number: bool,
+    /// -H forces the filename prefix, -h suppresses it,
+    /// by default it is printed only when searching more than one file
+    pub with_filename: Option<bool>,
+    /// -E, -i and --infix, how the pattern is written and matched
+    pub pattern: PatternOptions,
+    /// -x, select only lines which match as a whole
+    pub line_regexp: bool,
+}
+
+/// A parsed grep invocation
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrepConfig {
+    pub pattern: String,
+    /// files to search, `-` stands for stdin, empty means stdin
+    pub paths: Vec<String>,
+    pub options: GrepOptions,
+}
+
+/// parse the command line arguments (without the program name) of the search mode
+pub fn parse_args(args: &[String]) -> Result<GrepConfig, String> {
+    let mut options = GrepOptions::default();
+    let mut pattern_options = PatternOptions::default();
+
+    let mut positional = pattern_options::parse_flags(args, &mut pattern_options, |flag| {
+        match flag {
+            'c' => options.count = true,
+            'v' => options.invert = true,
+            'l' => options.files_with_matches = true,
+            'n' => options.line_number = true,
+            'H' => options.with_filename = Some(true),
+            'h' => options.with_filename = Some(false),
+            'x' => options.line_regexp = true,
+            _ => return false,
+        }
+        true
+    })?;
+    options.pattern = pattern_options;
+
+    if positional.is_empty() {
+        return Err("no pattern given".to_string());
+    }
+
+    let pattern = positional.remove(0);
+
+    Ok(GrepConfig {
+        pattern,
+        paths: positional,
+        options,
+    })
+}
+
+/// searches a single input, writing the selected lines to `out`
+/// returns the number of selected lines
+fn search_reader<R: BufRead, W: Write>(
+    regex: &Regex,
+    name: &str,
+    mut reader: R,
+    options: &GrepOptions,
+    show_filename: bool,
+    out: &mut W,
+) -> io::Result<usize> {
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+    let mut selected = 0;
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+        line_number += 1;
+
+        if buffer.ends_with(b"\n") {
+            buffer.pop();
+            if buffer.ends_with(b"\r") {
+                buffer.pop();
+            }
+        }
+
+        let line = String::from_utf8_lossy(&buffer);
+        if regex.is_match(&line) == options.invert {
+            continue;
+        }
+
+        selected += 1;
+
+        if options.files_with_matches {
+            // one selected line is enough to report the file
+            break;
+        }
+        if options.count {
+            continue;
+        }
+
+        if show_filename {
+            write!(out, "{}:", name)?;
+        }
+        if options.line_number {
+            write!(out, "{}:", line_number)?;
+        }
+        writeln!(out, "{}", line)?;
+    }
+
+    if options.files_with_matches {
+        if selected > 0 {
+            writeln!(out, "{}", name)?;
+        }
+    } else if options.count {
+        if show_filename {
+            write!(out, "{}:", name)?;
+        }
+        writeln!(out, "{}", selected)?;
+    }
+
+    Ok(selected)
+}
+
+/// compiles the pattern with the matching options of the search
+fn build_regex(pattern: &str, options: &GrepOptions) -> Result<Regex, RegexError> {
+    pattern_options::regex_builder(pattern, &options.pattern)
+        .anchored(options.line_regexp)
+        .build()
+}
+
+/// runs the search mode and returns the exit code,
+/// 0 if a line was selected, 1 if none was and 2 on errors
+pub fn run<W: Write>(config: &GrepConfig, out: &mut W) -> i32 {
+    let regex = match build_regex(&config.pattern, &config.options) {
+        Ok(regex) => regex,
+        Err(err) => {
+            eprintln!("grep-like-tool: {}", pattern_options::error_message(&err));
+            return 2;
+        }
+    };
+
+    let paths = if config.paths.is_empty() {
+        vec!["-".to_string()]
+    } else {
+        config.paths.clone()
+    };
+    let show_filename = config.options.with_filename.unwrap_or(paths.len() > 1);
+
+    let mut any_selected = false;
+    let mut any_error = false;
+
+    for path in paths.iter() {
+        let result = if path == "-" {
+            let stdin = io::stdin();
+            search_reader(
+                &regex,
+                STDIN_NAME,
+                stdin.lock(),
+                &config.options,
+                show_filename,
+                out,
+            )
+        } else {
+            File::open(path).and_then(|file| {
+                search_reader(
+                    &regex,
+                    path,
+                    BufReader::new(file),
+                    &config.options,
+                    show_filename,
+                    out,
+                )
+            })
+        };
+
+        match result {
+            Ok(selected) => any_selected |= selected > 0,
+            Err(err) => {
+                eprintln!("grep-like-tool: {}: {}", path, err);
+                any_error = true;
+            }
+        }
+    }
+
+    if any_error {
+        2
+    } else if any_selected {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn search(pattern: &str, input: &str, options: &GrepOptions, show_filename: bool) -> String {
+        let regex = build_regex(pattern, options).unwrap();
+        let mut out = Vec::new();
+        search_reader(
+            &regex,
+            "input.txt",
+            input.as_bytes(),
+            options,
+            show_filename,
+            &mut out,
+        )
+        .unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn check_parsing_arguments() {
+        let config = parse_args(&args(&["-cn", "-v", "star(symbol(a))", "a.txt", "-"])).unwrap();
+        assert_eq!(config.pattern, "star(symbol(a))");
+        assert_eq!(config.paths, args(&["a.txt", "-"]));
+        assert!(config.options.count);
+        assert!(config.options.line_number);
+        assert!(config.options.invert);
+        assert!(!config.options.files_with_matches);
+        assert_eq!(config.options.with_filename, None);
+
+        let config = parse_args(&args(&["-H", "--", "-l"])).unwrap();
+        assert_eq!(config.pattern, "-l");
+        assert_eq!(config.options.with_filename, Some(true));
+
+        let config = parse_args(&args(&["--infix", "ab*"])).unwrap();
+        assert!(config.options.pattern.infix);
+        let config = parse_args(&args(&["-En", "ab*"])).unwrap();
+        assert!(config.options.pattern.infix);
+        assert!(config.options.line_number);
+
+        let config = parse_args(&args(&["-ix", "symbol(a)"])).unwrap();
+        assert!(config.options.pattern.ignore_case);
+        assert!(config.options.line_regexp);
+
+        assert!(parse_args(&args(&["-q", "symbol(a)"])).is_err());
+        assert!(parse_args(&args(&["--bogus", "symbol(a)"])).is_err());
+        assert!(parse_args(&args(&["-c"])).is_err());
+    }
+
+    #[test]
+    fn check_line_contains_match() {
+        let options = GrepOptions::default();
+
+        let regex = build_regex("concat(symbol(a),symbol(b))", &options).unwrap();
+        assert!(regex.is_match("ab"));
+        assert!(regex.is_match("xxaby"));
+        assert!(regex.is_match("aaab"));
+        assert!(!regex.is_match("a b"));
+        assert!(!regex.is_match(""));
+
+        // star accepts the empty string, hence every line
+        let regex = build_regex("star(symbol(a))", &options).unwrap();
+        assert!(regex.is_match("xyz"));
+        assert!(regex.is_match(""));
+    }
+
+    #[test]
+    fn check_printing_selected_lines() {
+        let input = "abc\nxyz\nzab\n";
+        let pattern = "concat(symbol(a),symbol(b))";
+
+        let output = search(pattern, input, &GrepOptions::default(), false);
+        assert_eq!(output, "abc\nzab\n");
+
+        let options = GrepOptions {
+            line_number: true,
+            ..GrepOptions::default()
+        };
+        let output = search(pattern, input, &options, true);
+        assert_eq!(output, "input.txt:1:abc\ninput.txt:3:zab\n");
+
+        let options = GrepOptions {
+            invert: true,
+            ..GrepOptions::default()
+        };
+        let output = search(pattern, input, &options, false);
+        assert_eq!(output, "xyz\n");
+
+        let options = GrepOptions {
+            pattern: PatternOptions {
+                ignore_case: true,
+                ..PatternOptions::default()
+            },
+            ..GrepOptions::default()
+        };
+        let output = search(pattern, "ABC\nxyz\nzaB\n", &options, false);
+        assert_eq!(output, "ABC\nzaB\n");
+
+        let options = GrepOptions {
+            line_regexp: true,
+            ..GrepOptions::default()
+        };
+        let output = search(pattern, "abc\nab\nzab\n", &options, false);
+        assert_eq!(output, "ab\n");
+    }
+
+    #[test]
+    fn check_count_and_files_with_matches() {
+        let input = "abc\nxyz\r\nzab";
+        let pattern = "concat(symbol(a),symbol(b))";
+
+        let options = GrepOptions {
+            count: true,
+            ..GrepOptions::default()
+        };
+        assert_eq!(search(pattern, input, &options, false), "2\n");
+        assert_eq!(search(pattern, input, &options, true), "input.txt:2\n");
+
+        let options = GrepOptions {
+            files_with_matches: true,
+            ..GrepOptions::default()
+        };
+        assert_eq!(search(pattern, input, &options, false), "input.txt\n");
+        assert_eq!(search("symbol(q)", input, &options, false), "");
+    }
+}
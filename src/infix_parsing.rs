@@ -0,0 +1,559 @@
+//! This module contains the parser for reg-ex written in the conventional infix syntax
+//!
+//! ```text
+//! R -> R | R        union
+//! R -> R & R        intersection
+//! R -> R - R        difference
+//! R -> R R          concatenation, by juxtaposition
+//! R -> ~ R          complement
+//! R -> R *          kleene star
+//! R -> R +          one or more, `+` is never a union, so `a+b` is `a+` followed by `b`
+//! R -> R ?          zero or one
+//! R -> R {m,n}      at least m and at most n, `{m}` is exactly m and `{m,}` has no maximum
+//! R -> ( R )
+//! R -> ( )          the empty string
+//! R -> [ ]          the empty language, an empty character class
+//! R -> [ I ]        a character class like `[a-z_]`, `[^0-9]` is its complement
+//! R -> .            any single character
+//! R -> C            a literal character, `\` escapes any of `|+&-~*?{()[.\`,
+//!                   `\n`, `\t`, `\r`, `\0` and `\u{hex}` are escape sequences
+//! ```
+//!
+//! from the loosest to the tightest binding: union, then intersection and difference,
+//! concatenation, complement and the postfix operators
+//!
+//! unlike the `(a + b)^*c` notation of the README, `+` is not a union here, `|` is the
+//! only one, since `+` is also the postfix "one or more" and `a+b` could otherwise
+//! mean either `(a|b)` or `a+` followed by `b`
+
+use crate::{
+    dfa::DFA,
+    nfa::NFA,
+    parsing::{Cursor, ParsingError},
+    regex::{RegexError, DEFAULT_SIZE_LIMIT},
+    regex_ast::{RegexAst, INFIX_OPERATORS},
+};
+
+/// parsing an infix reg-ex into its syntax tree
+pub fn parse_infix_reg_ex(input: &str) -> Result<RegexAst, ParsingError> {
+    let mut parser = InfixParser {
+        cursor: Cursor::new(input),
+    };
+
+    let ast = parser.parse_union()?;
+
+    if !parser.cursor.is_at_end() {
+        // only an unbalanced `)` stops the parser early
+        return Err(parser
+            .cursor
+            .error("end of input, this `)` has no matching `(`"));
+    }
+
+    Ok(ast)
+}
+
+/// creating an NFA from an infix reg-ex, failing if it needs more than `DEFAULT_SIZE_LIMIT` states
+pub fn create_nfa_from_infix_reg_ex(input: &str) -> Result<NFA, RegexError> {
+    parse_infix_reg_ex(input)?
+        .to_nfa_with_size_limit(DEFAULT_SIZE_LIMIT)
+        .ok_or(RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        })
+}
+
+/// creating a DFA from an infix reg-ex, failing if it needs more than `DEFAULT_SIZE_LIMIT` states
+pub fn create_dfa_from_infix_reg_ex(input: &str) -> Result<DFA, RegexError> {
+    parse_infix_reg_ex(input)?
+        .to_dfa_with_size_limit(DEFAULT_SIZE_LIMIT)
+        .ok_or(RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        })
+}
+
+/// recursive descent parser, one function per precedence level
+struct InfixParser<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl InfixParser<'_> {
+    /// union := inter ('|' inter)*
+    fn parse_union(&mut self) -> Result<RegexAst, ParsingError> {
+        let mut alternatives = vec![self.parse_inter()?];
+
+        while let Some('|') = self.cursor.peek() {
+            self.cursor.bump();
+            alternatives.push(self.parse_inter()?);
+        }
+
+        Ok(balanced(alternatives, RegexAst::union))
+    }
+
+    /// inter := concat (('&' | '-') concat)*
+    fn parse_inter(&mut self) -> Result<RegexAst, ParsingError> {
+        let mut ast = self.parse_concat()?;
+        let mut levels = 0;
+
+        while let Some(operator @ ('&' | '-')) = self.cursor.peek() {
+            // a difference is not associative, so every operator nests the tree deeper
+            self.cursor.enter()?;
+            levels += 1;
+
+            self.cursor.bump();
+            let other = self.parse_concat()?;
+            ast = if operator == '&' {
+                RegexAst::intersection(ast, other)
+            } else {
+                RegexAst::difference(ast, other)
+            };
+        }
+        self.cursor.leave(levels);
+
+        Ok(ast)
+    }
+
+    /// concat := complement complement*
+    fn parse_concat(&mut self) -> Result<RegexAst, ParsingError> {
+        let mut operands = vec![self.parse_complement()?];
+
+        while let Some(ch) = self.cursor.peek() {
+            if matches!(ch, '|' | '&' | '-' | ')') {
+                break;
+            }
+
+            operands.push(self.parse_complement()?);
+        }
+
+        Ok(balanced(operands, RegexAst::concat))
+    }
+
+    /// complement := '~' complement | star
+    fn parse_complement(&mut self) -> Result<RegexAst, ParsingError> {
+        if self.cursor.peek() == Some('~') {
+            self.cursor.enter()?;
+            self.cursor.bump();
+            let inner = self.parse_complement()?;
+            self.cursor.leave(1);
+
+            return Ok(RegexAst::complement(inner));
+        }
+
+        self.parse_star()
+    }
+
+    /// star := atom ('*' | '+' | '?' | '{' count (',' count?)? '}')*
+    fn parse_star(&mut self) -> Result<RegexAst, ParsingError> {
+        let mut ast = self.parse_atom()?;
+        let mut levels = 0;
+
+        while let Some(operator @ ('*' | '+' | '?' | '{')) = self.cursor.peek() {
+            // every postfix operator wraps the operand once more
+            self.cursor.enter()?;
+            levels += 1;
+
+            ast = if operator == '{' {
+                let (min, max) = self.parse_bounds()?;
+                RegexAst::repeat(ast, min, max)
+            } else {
+                self.cursor.bump();
+                match operator {
+                    '*' => RegexAst::star(ast),
+                    '+' => RegexAst::plus(ast),
+                    _ => RegexAst::optional(ast),
+                }
+            };
+        }
+        self.cursor.leave(levels);
+
+        Ok(ast)
+    }
+
+    /// bounds := '{' count (',' count?)? '}'
+    fn parse_bounds(&mut self) -> Result<(u32, Option<u32>), ParsingError> {
+        let open_offset = self.cursor.offset();
+        self.cursor.bump();
+
+        let min = self
+            .cursor
+            .parse_count()?
+            .ok_or_else(|| self.cursor.error("the minimum count after `{`"))?;
+
+        let max = if self.cursor.peek() == Some(',') {
+            self.cursor.bump();
+            let max_offset = self.cursor.offset();
+            let max = self.cursor.parse_count()?;
+
+            if max.is_some_and(|max| max < min) {
+                return Err(self.cursor.error_at(
+                    max_offset,
+                    format!("a maximum count not below the minimum {}", min),
+                ));
+            }
+            max
+        } else {
+            Some(min)
+        };
+
+        self.cursor.expect(
+            '}',
+            &format!("`}}` to close the `{{` at offset {}", open_offset),
+        )?;
+
+        Ok((min, max))
+    }
+
+    /// atom := '(' ')' | '(' union ')' | '[' class | '.' | '\\' C | C
+    fn parse_atom(&mut self) -> Result<RegexAst, ParsingError> {
+        match self.cursor.peek() {
+            Some('(') if self.cursor.peek_nth(1) == Some(')') => {
+                self.cursor.bump();
+                self.cursor.bump();
+
+                Ok(RegexAst::Epsilon)
+            }
+            Some('(') => {
+                let open_offset = self.cursor.offset();
+                self.cursor.enter()?;
+                self.cursor.bump();
+                let ast = self.parse_union()?;
+
+                self.cursor.expect(
+                    ')',
+                    &format!("`)` to close the `(` at offset {}", open_offset),
+                )?;
+                self.cursor.leave(1);
+
+                Ok(ast)
+            }
+            Some('[') => {
+                self.cursor.bump();
+                let (ranges, negated) = self.cursor.parse_class(']')?;
+
+                Ok(RegexAst::class(ranges, negated))
+            }
+            Some('.') => {
+                self.cursor.bump();
+
+                Ok(RegexAst::Any)
+            }
+            Some('\\') => Ok(RegexAst::Symbol(
+                self.cursor.parse_char("a character after `\\`")?,
+            )),
+            Some(ch) if !INFIX_OPERATORS.contains(&ch) => {
+                self.cursor.bump();
+
+                Ok(RegexAst::Symbol(ch))
+            }
+            // missing operand, as in `a|`, `(|a)` or `*a`, `()` is the empty string above
+            _ => Err(self.cursor.error("a character or `(`")),
+        }
+    }
+}
+
+/// joins the operands of an associative operator pairwise, so that a long literal or
+/// a long list of alternatives gives a tree of logarithmic and not of linear depth
+fn balanced(mut operands: Vec<RegexAst>, join: fn(RegexAst, RegexAst) -> RegexAst) -> RegexAst {
+    while operands.len() > 1 {
+        let mut joined = Vec::with_capacity(operands.len().div_ceil(2));
+        let mut operands_iter = operands.into_iter();
+
+        while let Some(first) = operands_iter.next() {
+            joined.push(match operands_iter.next() {
+                Some(second) => join(first, second),
+                None => first,
+            });
+        }
+        operands = joined;
+    }
+
+    operands
+        .pop()
+        .unwrap_or_else(|| panic!("an operator has at least one operand"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::parsing::{create_dfa_from_reg_ex, parse_reg_ex, MAX_NESTING_DEPTH};
+
+    #[test]
+    fn check_extracting_symbols() {
+        let symbols = parse_infix_reg_ex("(a|b\\*)*c").unwrap().symbols();
+        assert_eq!(symbols, HashSet::from(['a', 'b', '*', 'c']));
+    }
+
+    #[test]
+    fn check_parsing_round_trip() {
+        for input in [
+            "(a|b)*c",
+            "ab|c*",
+            "a(b|c)d",
+            "a|(b|c)",
+            "\\(\\)**",
+            "[a-z_][^0-9\\]]*",
+            "\\[a]",
+            "a.*\\.",
+            "a|()|[]b",
+            "a-b*&~(c\\-)|~~d",
+            "~a+(b|c&d)-e",
+            "\\t[\\n\\--\\]]\\u{1b}",
+            "a+b?|c{2}d{3,}e{0,3}",
+            "a+*|\\{\\?",
+        ] {
+            let ast = parse_infix_reg_ex(input).unwrap();
+            assert_eq!(ast.infix().to_string(), input);
+
+            // the prefix form parses back into the same tree
+            assert_eq!(parse_reg_ex(&ast.to_string()).unwrap(), ast);
+        }
+    }
+
+    #[test]
+    fn check_precedence() {
+        // (a + b)*c
+        let dfa = create_dfa_from_infix_reg_ex("(a|b)*c").unwrap();
+        assert!(dfa.run("abbac").is_ok_and(|res| res));
+        assert!(dfa.run("c").is_ok_and(|res| res));
+        assert!(dfa.run("abcabc").is_ok_and(|res| !res));
+
+        // union binds weakest, star strongest
+        let dfa = create_dfa_from_infix_reg_ex("ab|c*").unwrap();
+        assert!(dfa.run("ab").is_ok_and(|res| res));
+        assert!(dfa.run("ccc").is_ok_and(|res| res));
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("abab").is_ok_and(|res| !res));
+        assert!(dfa.run("abc").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_escaped_operators() {
+        let dfa = create_dfa_from_infix_reg_ex("a\\*\\|").unwrap();
+        assert!(dfa.run("a*|").is_ok_and(|res| res));
+        assert!(dfa.run("aa|").is_ok_and(|res| !res));
+
+        for op in "|+&-~*?{()[.\\".chars() {
+            let input = format!("a\\{}b", op);
+            assert_eq!(
+                parse_infix_reg_ex(&input).unwrap(),
+                RegexAst::concat(
+                    RegexAst::concat(RegexAst::Symbol('a'), RegexAst::Symbol(op)),
+                    RegexAst::Symbol('b')
+                ),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn check_character_classes() {
+        let dfa = create_dfa_from_infix_reg_ex("[a-zA-Z_][a-zA-Z0-9_]*").unwrap();
+        assert!(dfa.run("camelCase_2").is_ok_and(|res| res));
+        assert!(dfa.run("2x").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_infix_reg_ex("[^ ]*").unwrap();
+        assert!(dfa.run("word").is_ok_and(|res| res));
+        assert!(dfa.run("two words").is_ok_and(|res| !res));
+
+        // `]` closes the class outside of it is a literal character
+        let dfa = create_dfa_from_infix_reg_ex("[a]]").unwrap();
+        assert!(dfa.run("a]").is_ok_and(|res| res));
+
+        assert_eq!(parse_infix_reg_ex("[]").unwrap(), RegexAst::Empty);
+        assert!(parse_infix_reg_ex("[a-").is_err());
+        assert!(parse_infix_reg_ex("[9-0]").is_err());
+    }
+
+    #[test]
+    fn check_any() {
+        let dfa = create_dfa_from_infix_reg_ex("a.*z").unwrap();
+        assert!(dfa.run("az").is_ok_and(|res| res));
+        assert!(dfa.run("a to z").is_ok_and(|res| res));
+        assert!(dfa.run("a to y").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_infix_reg_ex("\\.").unwrap();
+        assert!(dfa.run(".").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_repetitions() {
+        let dfa = create_dfa_from_infix_reg_ex("[0-9]{3}\\-?[0-9]{2,}").unwrap();
+        for (input, expected) in [
+            ("123-45", true),
+            ("12345678", true),
+            ("123-4", false),
+            ("12-345", false),
+        ] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        // `+` is always postfix, whatever follows it
+        let a_plus = || RegexAst::plus(RegexAst::Symbol('a'));
+        assert_eq!(parse_infix_reg_ex("a+").unwrap(), a_plus());
+        assert_eq!(
+            parse_infix_reg_ex("(ab)+|c").unwrap(),
+            RegexAst::union(
+                RegexAst::plus(RegexAst::concat(
+                    RegexAst::Symbol('a'),
+                    RegexAst::Symbol('b')
+                )),
+                RegexAst::Symbol('c')
+            )
+        );
+        assert_eq!(
+            parse_infix_reg_ex("a+b").unwrap(),
+            RegexAst::concat(a_plus(), RegexAst::Symbol('b'))
+        );
+        assert_eq!(
+            parse_infix_reg_ex("a+(b)").unwrap(),
+            RegexAst::concat(a_plus(), RegexAst::Symbol('b'))
+        );
+        assert_eq!(
+            parse_infix_reg_ex("a+|b").unwrap(),
+            RegexAst::union(a_plus(), RegexAst::Symbol('b'))
+        );
+        assert!(parse_infix_reg_ex("+a").is_err());
+
+        let err = parse_infix_reg_ex("a{3,2}").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.expected, "a maximum count not below the minimum 3");
+
+        let err = parse_infix_reg_ex("a{2").unwrap_err();
+        assert_eq!(err.position, 3);
+        assert_eq!(err.expected, "`}` to close the `{` at offset 1");
+    }
+
+    #[test]
+    fn check_epsilon_and_empty() {
+        let dfa = create_dfa_from_infix_reg_ex("a(b|())").unwrap();
+        assert!(dfa.run("a").is_ok_and(|res| res));
+        assert!(dfa.run("ab").is_ok_and(|res| res));
+
+        let dfa = create_dfa_from_infix_reg_ex("a[]|b").unwrap();
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+        assert!(dfa.run("b").is_ok_and(|res| res));
+
+        assert_eq!(parse_infix_reg_ex("()").unwrap(), RegexAst::Epsilon);
+        assert_eq!(parse_infix_reg_ex("[]").unwrap(), RegexAst::Empty);
+    }
+
+    #[test]
+    fn check_escape_sequences() {
+        let dfa = create_dfa_from_infix_reg_ex("a\\tb\\u{263A}").unwrap();
+        assert!(dfa.run("a\tb☺").is_ok_and(|res| res));
+        assert!(dfa.run("atb☺").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_infix_reg_ex("[^\\n]*\\n").unwrap();
+        assert!(dfa.run("line\n").is_ok_and(|res| res));
+        assert!(dfa.run("two\nlines\n").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_boolean_operators() {
+        // words of lowercase letters, other than the keywords
+        let dfa = create_dfa_from_infix_reg_ex("[a-z]+-(if|do)").unwrap();
+        for (input, expected) in [("x", true), ("iff", true), ("if", false), ("do", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        // complement binds tighter than concatenation
+        let dfa = create_dfa_from_infix_reg_ex("~ab").unwrap();
+        assert!(dfa.run("xb").is_ok_and(|res| res));
+        assert!(dfa.run("ab").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_infix_reg_ex(".*a.*&.*b.*").unwrap();
+        assert!(dfa.run("bxa").is_ok_and(|res| res));
+        assert!(dfa.run("aa").is_ok_and(|res| !res));
+
+        assert_eq!(
+            parse_infix_reg_ex("a&b|c-d").unwrap(),
+            RegexAst::union(
+                RegexAst::intersection(RegexAst::Symbol('a'), RegexAst::Symbol('b')),
+                RegexAst::difference(RegexAst::Symbol('c'), RegexAst::Symbol('d'))
+            )
+        );
+    }
+
+    #[test]
+    fn check_same_language_as_prefix_syntax() {
+        let infix = create_dfa_from_infix_reg_ex("01(0|1)*").unwrap();
+        let prefix = create_dfa_from_reg_ex(
+            "concat(concat(symbol(0),symbol(1)),star(union(symbol(0),symbol(1))))",
+        )
+        .unwrap();
+
+        for input in ["01", "010011", "1011", "0", ""] {
+            assert_eq!(infix.run(input).unwrap(), prefix.run(input).unwrap());
+        }
+    }
+
+    #[test]
+    fn check_error_positions() {
+        let err = parse_infix_reg_ex("a(b|c").unwrap_err();
+        assert_eq!(err.position, 5);
+        assert_eq!(err.found, None);
+        assert_eq!(err.expected, "`)` to close the `(` at offset 1");
+
+        let err = parse_infix_reg_ex("ab|*c").unwrap_err();
+        assert_eq!(err.position, 3);
+        assert_eq!(err.found, Some('*'));
+        assert_eq!(err.snippet(), "ab|*c\n   ^");
+
+        let err = parse_infix_reg_ex("é)").unwrap_err();
+        assert_eq!(err.position, 2);
+        assert_eq!(err.found, Some(')'));
+        assert_eq!(err.snippet(), "é)\n ^");
+    }
+
+    #[test]
+    fn check_size_limit() {
+        // the copies of the repetition are never built
+        let limit_exceeded = RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        };
+        assert_eq!(
+            create_nfa_from_infix_reg_ex("a{4000000000,}").unwrap_err(),
+            limit_exceeded
+        );
+        assert_eq!(
+            create_dfa_from_infix_reg_ex("a{4000000000,}").unwrap_err(),
+            limit_exceeded
+        );
+    }
+
+    #[test]
+    fn check_nesting_limit() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(create_nfa_from_infix_reg_ex(&nested(MAX_NESTING_DEPTH)).is_ok());
+
+        let err = parse_infix_reg_ex(&nested(50000)).unwrap_err();
+        assert_eq!(err.position, MAX_NESTING_DEPTH);
+        assert_eq!(err.found, Some('('));
+
+        let err = parse_infix_reg_ex(&format!("a{}", "*".repeat(50000))).unwrap_err();
+        assert_eq!(err.position, MAX_NESTING_DEPTH + 1);
+
+        // long concatenations and unions are balanced instead of nested
+        let ast = parse_infix_reg_ex(&"ab|".repeat(50000)[..149_999]).unwrap();
+        assert!(ast.to_nfa_with_size_limit(1000).is_none());
+        assert_eq!(
+            parse_infix_reg_ex("abcd").unwrap(),
+            RegexAst::concat(
+                RegexAst::concat(RegexAst::Symbol('a'), RegexAst::Symbol('b')),
+                RegexAst::concat(RegexAst::Symbol('c'), RegexAst::Symbol('d'))
+            )
+        );
+    }
+
+    #[test]
+    fn check_invalid_reg_ex() {
+        for input in [
+            "", "a|", "|a", "(|)", "(|a)", "*a", "+a", "(ab", "ab)", "a\\", "?a", "a{", "a{,2}",
+            "a{x}",
+        ] {
+            assert!(create_nfa_from_infix_reg_ex(input).is_err(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,824 @@
+//! This module contains the questions about the language of a DFA as a whole,
+//! such as whether two DFAs accept the same strings
+//!
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use crate::{
+    big_count::BigCount,
+    dfa::{BoolOp, DFA},
+    random::RandomSource,
+    state::State,
+    symbol_table::{next_char, Symbol},
+};
+
+impl DFA {
+    /// checks if both DFAs accept the same strings, otherwise returns
+    /// the shortest string accepted by exactly one of them
+    pub fn equivalent(&self, other: &DFA) -> Result<(), String> {
+        match self.product_with(other, BoolOp::Xor).shortest_accepted() {
+            Some(witness) => Err(witness),
+            None => Ok(()),
+        }
+    }
+
+    /// checks if every string accepted by `self` is accepted by `other`, otherwise
+    /// returns the shortest string accepted by `self` but not by `other`
+    pub fn is_subset_of(&self, other: &DFA) -> Result<(), String> {
+        match self
+            .product_with(other, BoolOp::Difference)
+            .shortest_accepted()
+        {
+            Some(witness) => Err(witness),
+            None => Ok(()),
+        }
+    }
+
+    /// checks if the DFA accepts no string at all
+    pub fn is_empty(&self) -> bool {
+        self.useful_states().is_empty()
+    }
+
+    /// checks if the DFA accepts every string
+    pub fn is_universal(&self) -> bool {
+        // a missing transition rejects the rest of the string like a dead state
+        self.reachable_states().iter().all(|state| {
+            self.final_states().contains(state)
+                && self.weighted_transitions(state).count() == self.nonempty_symbol_count()
+        })
+    }
+
+    /// checks if the DFA accepts finitely many strings
+    pub fn is_finite(&self) -> bool {
+        self.topological_order().is_some()
+    }
+
+    /// returns the number of strings the DFA accepts, `None` if there are infinitely many
+    pub fn language_size(&self) -> Option<BigCount> {
+        let order = self.topological_order()?;
+
+        // the number of accepted strings starting from each useful state,
+        // computed from the last state of the order backwards
+        let mut counts: HashMap<State, BigCount> = HashMap::new();
+        for &state in order.iter().rev() {
+            let mut count = if self.final_states().contains(&state) {
+                BigCount::one()
+            } else {
+                BigCount::zero()
+            };
+
+            for (num_chars, next_state) in self.weighted_transitions(&state) {
+                if let Some(next_count) = counts.get(&next_state) {
+                    count += &next_count.mul_small(num_chars);
+                }
+            }
+
+            counts.insert(state, count);
+        }
+
+        Some(
+            counts
+                .remove(&self.start_state())
+                .unwrap_or_else(BigCount::zero),
+        )
+    }
+
+    /// returns the number of accepted strings of exactly `len` characters
+    pub fn count_of_length(&self, len: usize) -> BigCount {
+        self.count_table(len)
+            .pop()
+            .and_then(|mut counts| counts.remove(&self.start_state()))
+            .unwrap_or_else(BigCount::zero)
+    }
+
+    /// returns the number of accepted strings of at most `len` characters
+    pub fn count_up_to(&self, len: usize) -> BigCount {
+        self.count_table(len)
+            .into_iter()
+            .filter_map(|mut counts| counts.remove(&self.start_state()))
+            .sum()
+    }
+
+    /// returns an accepted string of exactly `len` characters drawn uniformly at random,
+    /// `None` if no string of that length is accepted
+    ///
+    /// every step picks a character with probability proportional to the number of
+    /// accepted completions after it, so that every string is equally likely
+    pub fn sample<R: RandomSource>(&self, len: usize, rng: &mut R) -> Option<String> {
+        let table = self.count_table(len);
+        let mut state = self.start_state();
+        if !table[len].contains_key(&state) {
+            return None;
+        }
+
+        let symbols = self.ordered_symbols();
+        let mut sample = String::with_capacity(len);
+
+        for remaining in (0..len).rev() {
+            let mut target = rng.below_big(&table[remaining + 1][&state]);
+
+            for &(symbol, _) in symbols.iter() {
+                let num_chars = self.symbol_table().num_chars(symbol);
+                let next_state = match self.get_transition(&state, &symbol) {
+                    Some(next_state) if table[remaining].contains_key(&next_state) => next_state,
+                    _ => continue,
+                };
+                let block = table[remaining][&next_state].mul_small(num_chars);
+
+                match target.checked_sub(&block) {
+                    // the target is one of the strings through this symbol
+                    None => {
+                        let index = rng.below(num_chars as u64) as u32;
+                        sample.push(
+                            self.symbol_table()
+                                .nth_char(symbol, index)
+                                .unwrap_or_else(|| {
+                                    panic!("no character {} in {:?}", index, symbol)
+                                }),
+                        );
+                        state = next_state;
+                        break;
+                    }
+                    Some(rest) => target = rest,
+                }
+            }
+        }
+
+        Some(sample)
+    }
+
+    /// returns an accepted string of at most `max_len` characters found by a random walk,
+    /// `None` if no such string is accepted
+    ///
+    /// the walk stops or takes a symbol uniformly at random at every step, so unlike
+    /// `sample` short strings and rare symbols are favoured, which is cheap and suits
+    /// generating fuzzing inputs, the complement of the DFA gives rejected strings
+    pub fn random_walk<R: RandomSource>(&self, max_len: usize, rng: &mut R) -> Option<String> {
+        // within[k] holds the states accepting a string of at most k characters
+        let mut within: Vec<HashSet<State>> = vec![self.final_states().clone()];
+        for len in 1..=max_len {
+            let previous = &within[len - 1];
+            let states: HashSet<State> = self
+                .states()
+                .iter()
+                .copied()
+                .filter(|state| {
+                    self.final_states().contains(state)
+                        || self
+                            .weighted_transitions(state)
+                            .any(|(_, next_state)| previous.contains(&next_state))
+                })
+                .collect();
+            within.push(states);
+        }
+
+        let mut state = self.start_state();
+        if !within[max_len].contains(&state) {
+            return None;
+        }
+
+        let symbols = self.ordered_symbols();
+        let mut walk = String::new();
+
+        for remaining in (0..max_len).rev() {
+            let choices: Vec<(Symbol, State)> = symbols
+                .iter()
+                .filter_map(|&(symbol, _)| {
+                    let next_state = self.get_transition(&state, &symbol)?;
+                    let has_chars = self.symbol_table().num_chars(symbol) > 0;
+
+                    (has_chars && within[remaining].contains(&next_state))
+                        .then_some((symbol, next_state))
+                })
+                .collect();
+
+            // stopping counts as one more choice in a final state
+            let can_stop = self.final_states().contains(&state);
+            let num_choices = choices.len() + usize::from(can_stop);
+            if num_choices == 0 {
+                break;
+            }
+
+            let choice = rng.below(num_choices as u64) as usize;
+            if choice == choices.len() {
+                break;
+            }
+
+            let (symbol, next_state) = choices[choice];
+            let index = rng.below(self.symbol_table().num_chars(symbol) as u64) as u32;
+            walk.push(
+                self.symbol_table()
+                    .nth_char(symbol, index)
+                    .unwrap_or_else(|| panic!("no character {} in {:?}", index, symbol)),
+            );
+            state = next_state;
+        }
+
+        Some(walk)
+    }
+
+    /// returns for every length up to `max_len` the number of strings of that many
+    /// characters accepted from each state, leaving out the states accepting none
+    fn count_table(&self, max_len: usize) -> Vec<HashMap<State, BigCount>> {
+        let mut table: Vec<HashMap<State, BigCount>> = Vec::with_capacity(max_len + 1);
+        table.push(
+            self.final_states()
+                .iter()
+                .map(|&state| (state, BigCount::one()))
+                .collect(),
+        );
+
+        for len in 1..=max_len {
+            let previous = &table[len - 1];
+            let mut counts: HashMap<State, BigCount> = HashMap::new();
+
+            for state in self.states().iter() {
+                let mut count = BigCount::zero();
+                for (num_chars, next_state) in self.weighted_transitions(state) {
+                    if let Some(next_count) = previous.get(&next_state) {
+                        count += &next_count.mul_small(num_chars);
+                    }
+                }
+
+                if !count.is_zero() {
+                    counts.insert(*state, count);
+                }
+            }
+
+            table.push(counts);
+        }
+
+        table
+    }
+
+    /// returns an iterator over the accepted strings of at most `max_len` characters,
+    /// the shorter ones first and those of the same length in lexicographic order
+    pub fn enumerate(&self, max_len: usize) -> ShortlexIter<'_> {
+        ShortlexIter {
+            dfa: self,
+            segments: self.symbol_table().segments(),
+            max_len,
+            finishing: vec![self.final_states().clone()],
+            current: Vec::new(),
+            length: None,
+        }
+    }
+
+    /// returns the states reached by the transitions of a state together with the number
+    /// of characters making each of them, leaving out symbols without any character
+    fn weighted_transitions<'a>(
+        &'a self,
+        state: &'a State,
+    ) -> impl Iterator<Item = (u32, State)> + 'a {
+        self.symbol_table().input_symbols().filter_map(|symbol| {
+            let num_chars = self.symbol_table().num_chars(symbol);
+            let next_state = self.get_transition(state, &symbol)?;
+
+            (num_chars > 0).then_some((num_chars, next_state))
+        })
+    }
+
+    /// returns the number of input symbols standing for at least one character
+    fn nonempty_symbol_count(&self) -> usize {
+        self.symbol_table()
+            .input_symbols()
+            .filter(|&symbol| self.symbol_table().num_chars(symbol) > 0)
+            .count()
+    }
+
+    /// returns the states reachable from the start state
+    fn reachable_states(&self) -> HashSet<State> {
+        let mut reachable = HashSet::from([self.start_state()]);
+        let mut q: VecDeque<State> = VecDeque::from([self.start_state()]);
+
+        while let Some(state) = q.pop_front() {
+            for (_, next_state) in self.weighted_transitions(&state) {
+                if reachable.insert(next_state) {
+                    q.push_back(next_state);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// returns the states which are reachable from the start state
+    /// and from which a final state can be reached
+    pub(crate) fn useful_states(&self) -> HashSet<State> {
+        let reachable = self.reachable_states();
+
+        let mut predecessors: HashMap<State, Vec<State>> = HashMap::new();
+        for state in reachable.iter() {
+            for (_, next_state) in self.weighted_transitions(state) {
+                predecessors.entry(next_state).or_default().push(*state);
+            }
+        }
+
+        let mut useful: HashSet<State> = self
+            .final_states()
+            .intersection(&reachable)
+            .copied()
+            .collect();
+        let mut q: VecDeque<State> = useful.iter().copied().collect();
+
+        while let Some(state) = q.pop_front() {
+            for &previous_state in predecessors.get(&state).into_iter().flatten() {
+                if useful.insert(previous_state) {
+                    q.push_back(previous_state);
+                }
+            }
+        }
+
+        useful
+    }
+
+    /// orders the useful states so that every transition between them goes forward,
+    /// `None` if they lie on a cycle, which means the language is infinite
+    fn topological_order(&self) -> Option<Vec<State>> {
+        let useful = self.useful_states();
+
+        let mut in_degree: HashMap<State, usize> = useful.iter().map(|&state| (state, 0)).collect();
+        for state in useful.iter() {
+            for (_, next_state) in self.weighted_transitions(state) {
+                if let Some(degree) = in_degree.get_mut(&next_state) {
+                    *degree += 1;
+                }
+            }
+        }
+
+        let mut q: VecDeque<State> = in_degree
+            .iter()
+            .filter(|&(_, &degree)| degree == 0)
+            .map(|(&state, _)| state)
+            .collect();
+        let mut order = Vec::with_capacity(useful.len());
+
+        while let Some(state) = q.pop_front() {
+            order.push(state);
+
+            for (_, next_state) in self.weighted_transitions(&state) {
+                if let Some(degree) = in_degree.get_mut(&next_state) {
+                    *degree -= 1;
+                    if *degree == 0 {
+                        q.push_back(next_state);
+                    }
+                }
+            }
+        }
+
+        (order.len() == useful.len()).then_some(order)
+    }
+
+    /// returns the shortest accepted string, `None` if the DFA accepts no string
+    pub fn shortest_accepted(&self) -> Option<String> {
+        self.shortest_string(|state| {
+            state.is_some_and(|state| self.final_states().contains(&state))
+        })
+    }
+
+    /// returns the shortest rejected string, `None` if the DFA accepts every string
+    pub fn shortest_rejected(&self) -> Option<String> {
+        self.shortest_string(|state| {
+            !state.is_some_and(|state| self.final_states().contains(&state))
+        })
+    }
+
+    /// product of two DFAs whose symbol tables are merged if they differ
+    fn product_with(&self, other: &DFA, op: BoolOp) -> DFA {
+        // the merged symbol table refines both tables, so moving onto it cannot fail
+        self.product(other, op)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// returns the input symbols with a character standing for each,
+    /// ordered by that character so that the searches are deterministic,
+    /// Symbol::Other is left out only when the table covers every character
+    fn ordered_symbols(&self) -> Vec<(Symbol, char)> {
+        let mut symbols: Vec<(Symbol, char)> = self
+            .symbol_table()
+            .input_symbols()
+            .filter(|&symbol| self.symbol_table().num_chars(symbol) > 0)
+            .map(|symbol| {
+                let representative = self
+                    .symbol_table()
+                    .representative(symbol)
+                    .unwrap_or_else(|| panic!("no character moves on {:?}", symbol));
+                (symbol, representative)
+            })
+            .collect();
+        symbols.sort_by_key(|&(_, ch)| ch);
+
+        symbols
+    }
+
+    /// breadth first search from the start state for the shortest string leading to
+    /// a state for which `is_target` holds, where `None` is the dead state reached by
+    /// a missing transition, ties are broken by the characters standing for the symbols
+    fn shortest_string(&self, is_target: impl Fn(Option<State>) -> bool) -> Option<String> {
+        let symbols = self.ordered_symbols();
+        let start_state = Some(self.start_state());
+
+        // state -> the state and character it was first reached from
+        let mut parent: HashMap<Option<State>, Option<(Option<State>, char)>> = HashMap::new();
+        parent.insert(start_state, None);
+
+        let mut q: VecDeque<Option<State>> = VecDeque::new();
+        q.push_back(start_state);
+
+        while let Some(state) = q.pop_front() {
+            if is_target(state) {
+                let mut chars = Vec::new();
+                let mut current = state;
+
+                while let Some((previous, ch)) = parent[&current] {
+                    chars.push(ch);
+                    current = previous;
+                }
+
+                return Some(chars.into_iter().rev().collect());
+            }
+
+            for &(symbol, ch) in symbols.iter() {
+                let next_state = state.and_then(|state| self.get_transition(&state, &symbol));
+
+                if let Entry::Vacant(entry) = parent.entry(next_state) {
+                    entry.insert(Some((state, ch)));
+                    q.push_back(next_state);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterator over the accepted strings of a DFA in shortlex order
+pub struct ShortlexIter<'d> {
+    dfa: &'d DFA,
+    // every character with the symbol it moves on, in order
+    segments: Vec<(char, char, Symbol)>,
+    max_len: usize,
+    // finishing[k] holds the states from which a string of exactly k characters is accepted
+    finishing: Vec<HashSet<State>>,
+    // the last string returned, as its characters with the state before each of them
+    current: Vec<(State, char)>,
+    // the length of the strings being returned, `None` before the first one
+    length: Option<usize>,
+}
+
+impl Iterator for ShortlexIter<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let found = match self.length {
+            Some(length) => self.advance(length),
+            None => false,
+        };
+
+        if !found {
+            // the smallest string of the next length with an accepted one
+            let mut length = self.length.map_or(0, |length| length + 1);
+
+            loop {
+                if length > self.max_len {
+                    self.length = Some(length);
+                    return None;
+                }
+                let start_state = self.dfa.start_state();
+                if self.finishing(length).contains(&start_state) {
+                    break;
+                }
+                length += 1;
+            }
+
+            self.length = Some(length);
+            self.current.clear();
+            self.fill(self.dfa.start_state(), length);
+        }
+
+        Some(self.current.iter().map(|&(_, ch)| ch).collect())
+    }
+}
+
+impl ShortlexIter<'_> {
+    /// returns the states from which a string of exactly `length` characters is accepted
+    fn finishing(&mut self, length: usize) -> &HashSet<State> {
+        while self.finishing.len() <= length {
+            let previous = &self.finishing[self.finishing.len() - 1];
+            let states: HashSet<State> = self
+                .dfa
+                .states()
+                .iter()
+                .copied()
+                .filter(|state| {
+                    self.dfa
+                        .weighted_transitions(state)
+                        .any(|(_, next_state)| previous.contains(&next_state))
+                })
+                .collect();
+
+            self.finishing.push(states);
+        }
+
+        &self.finishing[length]
+    }
+
+    /// returns the smallest character after `after`, or the smallest of all, which moves
+    /// from `state` to a state accepting a string of exactly `remaining` characters
+    fn smallest_step(
+        &mut self,
+        state: State,
+        after: Option<char>,
+        remaining: usize,
+    ) -> Option<(char, State)> {
+        let lowest = match after {
+            Some(ch) => next_char(ch)?,
+            None => '\0',
+        };
+        self.finishing(remaining);
+
+        for &(start, end, symbol) in self.segments.iter() {
+            if end < lowest {
+                continue;
+            }
+
+            let next_state = match self.dfa.get_transition(&state, &symbol) {
+                Some(next_state) if self.finishing[remaining].contains(&next_state) => next_state,
+                _ => continue,
+            };
+
+            let ch = start.max(lowest);
+            if ch <= end {
+                return Some((ch, next_state));
+            }
+        }
+
+        None
+    }
+
+    /// appends the smallest string of `remaining` characters accepted from `state`
+    fn fill(&mut self, mut state: State, remaining: usize) {
+        for remaining in (0..remaining).rev() {
+            let (ch, next_state) = self
+                .smallest_step(state, None, remaining)
+                .unwrap_or_else(|| panic!("state {} accepts no string of this length", state));
+
+            self.current.push((state, ch));
+            state = next_state;
+        }
+    }
+
+    /// moves `current` to the next accepted string of the same length, returns false
+    /// if it was the last one
+    fn advance(&mut self, length: usize) -> bool {
+        while let Some((state, ch)) = self.current.pop() {
+            let remaining = length - self.current.len() - 1;
+
+            if let Some((next_ch, next_state)) = self.smallest_step(state, Some(ch), remaining) {
+                self.current.push((state, next_ch));
+                self.fill(next_state, remaining);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parsing::create_dfa_from_reg_ex, random::SplitMix64};
+
+    use super::*;
+
+    #[test]
+    fn check_equivalence() {
+        // (a|b)* written in two ways
+        let first = create_dfa_from_reg_ex("star(union(symbol(a),symbol(b)))").unwrap();
+        let second =
+            create_dfa_from_reg_ex("star(concat(star(symbol(a)),star(symbol(b))))").unwrap();
+        assert_eq!(first.equivalent(&second), Ok(()));
+        assert_eq!(second.equivalent(&first), Ok(()));
+
+        // the shortest string telling them apart is b
+        let third = create_dfa_from_reg_ex("star(symbol(a))").unwrap();
+        assert_eq!(first.equivalent(&third), Err("b".to_string()));
+
+        let fourth = create_dfa_from_reg_ex("concat(symbol(a),star(symbol(a)))").unwrap();
+        assert_eq!(third.equivalent(&fourth), Err("".to_string()));
+    }
+
+    #[test]
+    fn check_equivalence_over_different_symbol_tables() {
+        let letters = create_dfa_from_reg_ex("class(a-z)").unwrap();
+        let split = create_dfa_from_reg_ex("union(class(a-m),class(n-z))").unwrap();
+        assert_eq!(letters.equivalent(&split), Ok(()));
+
+        // the characters outside both tables are told apart too
+        let any = create_dfa_from_reg_ex("any()").unwrap();
+        let witness = any.equivalent(&letters).unwrap_err();
+        assert_eq!(witness.chars().count(), 1);
+        assert!(any.run(&witness).is_ok_and(|res| res));
+        assert!(letters.run(&witness).is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_control_character_witness() {
+        // the printable characters leave only the control characters to Symbol::Other
+        let printable = create_dfa_from_reg_ex("star(class(\u{20}-\u{10FFFF}))").unwrap();
+        let any = create_dfa_from_reg_ex("star(any())").unwrap();
+
+        assert_eq!(any.equivalent(&printable), Err("\0".to_string()));
+        assert_eq!(any.is_subset_of(&printable), Err("\0".to_string()));
+        assert_eq!(printable.is_subset_of(&any), Ok(()));
+        assert_eq!(printable.shortest_rejected(), Some("\0".to_string()));
+    }
+
+    #[test]
+    fn check_inclusion() {
+        let old_allowlist = create_dfa_from_reg_ex("plus(class(a-z0-9_))").unwrap();
+        let new_allowlist =
+            create_dfa_from_reg_ex("concat(class(a-z),star(class(a-z0-9)))").unwrap();
+
+        assert_eq!(new_allowlist.is_subset_of(&old_allowlist), Ok(()));
+        assert_eq!(
+            old_allowlist.is_subset_of(&new_allowlist),
+            Err("0".to_string())
+        );
+
+        // every language contains the empty one, and is contained in itself
+        let empty = create_dfa_from_reg_ex("empty()").unwrap();
+        assert_eq!(empty.is_subset_of(&new_allowlist), Ok(()));
+        assert_eq!(new_allowlist.is_subset_of(&new_allowlist), Ok(()));
+        assert_eq!(new_allowlist.is_subset_of(&empty), Err("a".to_string()));
+    }
+
+    #[test]
+    fn check_emptiness_and_universality() {
+        for (reg_ex, is_empty, is_universal) in [
+            ("empty()", true, false),
+            ("eps()", false, false),
+            ("inter(symbol(a),symbol(b))", true, false),
+            ("star(any())", false, true),
+            ("union(star(symbol(a)),not(star(symbol(a))))", false, true),
+            ("star(class(^a))", false, false),
+        ] {
+            let dfa = create_dfa_from_reg_ex(reg_ex).unwrap();
+            assert_eq!(dfa.is_empty(), is_empty, "{}", reg_ex);
+            assert_eq!(dfa.is_universal(), is_universal, "{}", reg_ex);
+        }
+    }
+
+    #[test]
+    fn check_finiteness_and_size() {
+        for (reg_ex, size) in [
+            ("empty()", Some(0)),
+            ("eps()", Some(1)),
+            ("repeat(class(a-c),0,2)", Some(1 + 3 + 9)),
+            ("union(symbol(a),class(a-b))", Some(2)),
+            ("concat(star(symbol(a)),empty())", Some(0)),
+            ("star(symbol(a))", None),
+            ("concat(symbol(a),star(class(x-z)))", None),
+        ] {
+            let dfa = create_dfa_from_reg_ex(reg_ex).unwrap();
+            assert_eq!(dfa.is_finite(), size.is_some(), "{}", reg_ex);
+            assert_eq!(dfa.language_size(), size.map(BigCount::from), "{}", reg_ex);
+        }
+
+        // every character counts, including the ones the reg-ex does not mention
+        let dfa = create_dfa_from_reg_ex("repeat(any(),3,3)").unwrap();
+        let num_chars = BigCount::from(0x110000 - 0x800);
+        let expected = num_chars
+            .mul_small(0x110000 - 0x800)
+            .mul_small(0x110000 - 0x800);
+        assert_eq!(dfa.language_size(), Some(expected));
+    }
+
+    #[test]
+    fn check_enumeration_in_shortlex_order() {
+        let dfa =
+            create_dfa_from_reg_ex("star(union(symbol(b),concat(symbol(a),symbol(c))))").unwrap();
+        let strings: Vec<String> = dfa.enumerate(3).collect();
+        assert_eq!(strings, vec!["", "b", "ac", "bb", "acb", "bac", "bbb"]);
+
+        // ranges and negated classes are enumerated character by character
+        let dfa = create_dfa_from_reg_ex("concat(class(x-z),opt(symbol(0)))").unwrap();
+        let strings: Vec<String> = dfa.enumerate(5).collect();
+        assert_eq!(strings, vec!["x", "y", "z", "x0", "y0", "z0"]);
+
+        let dfa = create_dfa_from_reg_ex("not(star(class(^a)))").unwrap();
+        let strings: Vec<String> = dfa.enumerate(2).take(3).collect();
+        assert_eq!(strings, vec!["a", "\0a", "\u{1}a"]);
+
+        // the surrogates are skipped
+        let dfa = create_dfa_from_reg_ex("class(\\u{D7FE}-\\u{E001})").unwrap();
+        let strings: Vec<String> = dfa.enumerate(1).collect();
+        assert_eq!(
+            strings,
+            vec!["\u{D7FE}", "\u{D7FF}", "\u{E000}", "\u{E001}"]
+        );
+
+        let dfa = create_dfa_from_reg_ex("empty()").unwrap();
+        assert_eq!(dfa.enumerate(10).next(), None);
+    }
+
+    #[test]
+    fn check_counting_by_length() {
+        // identifiers of a lowercase letter followed by letters and digits
+        let dfa = create_dfa_from_reg_ex("concat(class(a-z),star(class(a-z0-9)))").unwrap();
+        assert_eq!(dfa.count_of_length(0), BigCount::zero());
+        assert_eq!(dfa.count_of_length(1), BigCount::from(26));
+        assert_eq!(dfa.count_of_length(2), BigCount::from(26 * 36));
+        assert_eq!(dfa.count_up_to(2), BigCount::from(26 + 26 * 36));
+
+        // 26 * 36^7 tokens of 8 characters, and the count for 30 needs more than a u128
+        assert_eq!(dfa.count_of_length(8), BigCount::from(26 * 36u64.pow(7)));
+        assert_eq!(dfa.count_of_length(30).to_u128(), None);
+
+        // agrees with the enumeration and the size of finite languages
+        let dfa =
+            create_dfa_from_reg_ex("repeat(union(symbol(a),concat(symbol(b),symbol(c))),0,3)")
+                .unwrap();
+        for len in 0..=7 {
+            assert_eq!(
+                dfa.count_up_to(len),
+                BigCount::from(dfa.enumerate(len).count() as u64)
+            );
+        }
+        assert_eq!(Some(dfa.count_up_to(6)), dfa.language_size());
+    }
+
+    #[test]
+    fn check_uniform_sampling() {
+        let mut rng = SplitMix64::new(1);
+
+        // 2 strings through a and 4 through b, each should be drawn about 1000 times
+        let dfa = create_dfa_from_reg_ex(
+            "union(concat(symbol(a),class(x-y)),concat(symbol(b),class(0-3)))",
+        )
+        .unwrap();
+        let mut frequencies: HashMap<String, usize> = HashMap::new();
+        for _ in 0..6000 {
+            let sample = dfa.sample(2, &mut rng).unwrap();
+            assert!(dfa.run(&sample).is_ok_and(|res| res));
+            *frequencies.entry(sample).or_default() += 1;
+        }
+
+        assert_eq!(frequencies.len(), 6);
+        for (sample, &frequency) in frequencies.iter() {
+            assert!((800..1200).contains(&frequency), "{} {}", sample, frequency);
+        }
+
+        assert_eq!(dfa.sample(1, &mut rng), None);
+
+        // characters outside the symbol table are drawn too
+        let dfa = create_dfa_from_reg_ex("repeat(class(^a),20,20)").unwrap();
+        let sample = dfa.sample(20, &mut rng).unwrap();
+        assert_eq!(sample.chars().count(), 20);
+        assert!(!sample.contains('a'));
+    }
+
+    #[test]
+    fn check_random_walk() {
+        let mut rng = SplitMix64::new(2);
+
+        let dfa =
+            create_dfa_from_reg_ex("concat(symbol(a),star(union(symbol(b),symbol(c))))").unwrap();
+        for _ in 0..100 {
+            let walk = dfa.random_walk(6, &mut rng).unwrap();
+            assert!(walk.chars().count() <= 6);
+            assert!(dfa.run(&walk).is_ok_and(|res| res), "{}", walk);
+        }
+
+        // rejected strings from the complement
+        let complement = dfa.complement();
+        for _ in 0..100 {
+            let walk = complement.random_walk(6, &mut rng).unwrap();
+            assert!(dfa.run(&walk).is_ok_and(|res| !res), "{}", walk);
+        }
+
+        let dfa = create_dfa_from_reg_ex("repeat(symbol(a),3,3)").unwrap();
+        assert_eq!(dfa.random_walk(2, &mut rng), None);
+        assert_eq!(dfa.random_walk(3, &mut rng), Some("aaa".to_string()));
+    }
+
+    #[test]
+    fn check_shortest_strings() {
+        for (reg_ex, accepted, rejected) in [
+            ("concat(symbol(a),symbol(b))", Some("ab"), Some("")),
+            ("star(symbol(a))", Some(""), Some("b")),
+            (
+                "union(concat(symbol(b),symbol(b)),concat(symbol(a),star(symbol(c))))",
+                Some("a"),
+                Some(""),
+            ),
+            ("plus(class(^x))", Some("a"), Some("")),
+            ("star(any())", Some(""), None),
+            ("empty()", None, Some("")),
+        ] {
+            let dfa = create_dfa_from_reg_ex(reg_ex).unwrap();
+            assert_eq!(dfa.shortest_accepted().as_deref(), accepted, "{}", reg_ex);
+            assert_eq!(dfa.shortest_rejected().as_deref(), rejected, "{}", reg_ex);
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Regular expressions compiled to minimized DFAs
+//!
+//! [`Regex`] compiles a pattern once and matches it against many strings,
+//! the automata it is built from are available for other constructions
+//!
+//! ```
+//! use grep_like_tool::Regex;
+//!
+//! let regex = Regex::new("concat(symbol(a),star(symbol(b)))").unwrap();
+//! assert!(regex.is_match("xabbx"));
+//! assert_eq!(regex.find("xabbx").unwrap().range(), 1..4);
+//! ```
+
+pub mod big_count;
+pub mod custom_errors;
+mod d_transition_function;
+pub mod dfa;
+mod disjoint_set_union;
+pub mod infix_parsing;
+pub mod language;
+mod n_transition_function;
+pub mod nfa;
+pub mod parsing;
+pub mod random;
+pub mod regex;
+pub mod regex_ast;
+pub mod search;
+pub mod state;
+mod state_elimination;
+pub mod symbol_table;
+mod transition_function;
+
+pub use big_count::BigCount;
+pub use dfa::{BoolOp, DFA};
+pub use language::ShortlexIter;
+pub use nfa::NFA;
+pub use parsing::ParsingError;
+pub use random::{RandomSource, SplitMix64};
+pub use regex::{Regex, RegexBuilder, RegexError, Split, Syntax};
+pub use regex_ast::RegexAst;
+pub use search::{FindIter, Match};
+pub use symbol_table::{Symbol, SymbolTable};
@@ -1,20 +1,68 @@
-use std::io::{self, BufRead};
-
-mod custom_errors;
-mod d_transition_function;
-mod dfa;
-mod disjoint_set_union;
-mod n_transition_function;
-mod nfa;
-mod parsing;
-mod state;
-mod symbol_table;
-mod transition_function;
-// mod finite_automaton;
-// mod n_transition_function;
-// mod nfa;
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    process,
+};
+
+use grep_like_tool::{parsing, RegexError};
+
+mod analysis;
+mod grep;
+mod pattern_options;
+
+const USAGE: &str = "usage: grep-like-tool [-cvlnHhEix] [--infix] PATTERN [FILE...]
+       grep-like-tool equiv [-Ei] [--infix] PATTERN PATTERN
+       grep-like-tool enumerate [-Ei] [--infix] PATTERN [MAX_LEN]
+       grep-like-tool info [-Ei] [--infix] PATTERN
+       grep-like-tool --batch < input.txt";
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if args.first().is_some_and(|arg| arg == "--batch") {
+        run_batch();
+        return;
+    }
+
+    if args
+        .first()
+        .is_some_and(|arg| analysis::COMMANDS.contains(&arg.as_str()))
+    {
+        run_analysis(&args);
+    }
+
+    let config = grep::parse_args(&args).unwrap_or_else(|err| {
+        eprintln!("grep-like-tool: {}", err);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let exit_code = grep::run(&config, &mut out);
+    let _ = out.flush();
+
+    process::exit(exit_code);
+}
+
+/// runs a subcommand inspecting the languages of patterns and exits
+fn run_analysis(args: &[String]) -> ! {
+    let config = analysis::parse_args(args).unwrap_or_else(|err| {
+        eprintln!("grep-like-tool: {}", err);
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let exit_code = analysis::run(&config, &mut out);
+    let _ = out.flush();
+
+    process::exit(exit_code);
+}
+
+/// the original input format, a count followed by pairs of reg-ex and test string
+fn run_batch() {
     let stdin = io::stdin();
     let mut iter = stdin.lock().lines();
 
@@ -24,11 +72,11 @@ fn main() {
             panic!("No number of test cases given");
         })
         .unwrap_or_else(|err| {
-            panic!("Error in std input, {}", err.to_string());
+            panic!("Error in std input, {}", err);
         })
         .parse::<usize>()
         .unwrap_or_else(|err| {
-            panic!("Error in parsing number {}", err.to_string());
+            panic!("Error in parsing number {}", err);
         });
 
     for _ in 0..num_test_cases {
@@ -38,7 +86,7 @@ fn main() {
                 panic!("No number of test cases given");
             })
             .unwrap_or_else(|err| {
-                panic!("Error in std input, {}", err.to_string());
+                panic!("Error in std input, {}", err);
             });
         let input_string = iter
             .next()
@@ -46,14 +94,17 @@ fn main() {
                 panic!("No number of test cases given");
             })
             .unwrap_or_else(|err| {
-                panic!("Error in std input {}", err.to_string());
+                panic!("Error in std input {}", err);
             });
 
         let dfa = parsing::create_dfa_from_reg_ex(&regex);
         let dfa = match dfa {
             Ok(dfa) => dfa,
             Err(err) => {
-                println!("{}", err.to_string());
+                println!("{}", err);
+                if let RegexError::Parse(err) = err {
+                    println!("{}", err.snippet());
+                }
                 continue;
             }
         };
@@ -63,7 +114,7 @@ fn main() {
                 println!("{}", if res { "Yes" } else { "No" });
             }
             Err(err) => {
-                println!("{}", err.to_string());
+                println!("{}", err);
             }
         }
     }
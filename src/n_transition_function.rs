@@ -1,7 +1,7 @@
 use crate::{
     custom_errors::{AutomatonError, NFAError},
     state::State,
-    symbol_table::Symbol,
+    symbol_table::{Symbol, SymbolTable},
     transition_function::BasicFunctionsForTransitions,
 };
 use std::collections::{HashMap, HashSet};
@@ -18,7 +18,7 @@ impl BasicFunctionsForTransitions for NTransitionFunction {
 
     fn extend(&mut self, increment: usize) {
         //
-        let mut on_states: Vec<State> = self.f.keys().map(|&val| val).collect();
+        let mut on_states: Vec<State> = self.f.keys().copied().collect();
         on_states.sort();
         // going through keys in decreasing order, in order to avoid overlapping issue
         on_states.reverse();
@@ -51,8 +51,8 @@ impl BasicFunctionsForTransitions for NTransitionFunction {
         symbol: &Symbol,
         next_state: &State,
     ) -> Result<(), AutomatonError> {
-        let state_transitions = self.f.entry(*state).or_insert(HashMap::new());
-        let state_symbol_transitions = state_transitions.entry(*symbol).or_insert(HashSet::new());
+        let state_transitions = self.f.entry(*state).or_default();
+        let state_symbol_transitions = state_transitions.entry(*symbol).or_default();
 
         if state_symbol_transitions.contains(next_state) {
             return Err(AutomatonError::NFAError(NFAError::ExistingTransition(
@@ -69,10 +69,10 @@ impl NTransitionFunction {
     /// takes in self and another NTransitionFunction and returns the combined transition table of the 2
     pub fn combine_transition(mut self, other: &Self) -> Self {
         for (&state, other_transitions) in other.f.iter() {
-            let existing_transitions = self.f.entry(state).or_insert(HashMap::new());
+            let existing_transitions = self.f.entry(state).or_default();
 
             for (&symbol, next_states) in other_transitions.iter() {
-                let entry = existing_transitions.entry(symbol).or_insert(HashSet::new());
+                let entry = existing_transitions.entry(symbol).or_default();
                 for &next_state in next_states.iter() {
                     entry.insert(next_state);
                 }
@@ -84,7 +84,41 @@ impl NTransitionFunction {
 
     /// to check if a transition is valid, on a state and symbol
     pub fn is_valid_transition(&self, state: &State, symbol: &Symbol) -> bool {
-        self.f.contains_key(&state) && self.f[state].contains_key(symbol)
+        self.f.contains_key(state) && self.f[state].contains_key(symbol)
+    }
+
+    /// moves the transitions on the symbols of `from` to the symbols of `to`, which has to
+    /// refine `from`, a transition on a symbol becomes one on every symbol inside it
+    pub fn remap(&self, from: &SymbolTable, to: &SymbolTable) -> Result<Self, AutomatonError> {
+        let refinement = from.refinement(to).ok_or_else(|| {
+            AutomatonError::SymbolTableMismatch(
+                "the new symbol table does not refine the old one".to_string(),
+            )
+        })?;
+
+        let mut symbol_to_new_symbols: HashMap<Symbol, Vec<Symbol>> = HashMap::new();
+        for (&new_symbol, &symbol) in refinement.iter() {
+            symbol_to_new_symbols
+                .entry(symbol)
+                .or_default()
+                .push(new_symbol);
+        }
+
+        let mut transition_function = NTransitionFunction::new();
+        for (&state, symbol_to_next_state_set_map) in self.f.iter() {
+            let new_transitions = transition_function.f.entry(state).or_default();
+
+            for (symbol, next_state_set) in symbol_to_next_state_set_map.iter() {
+                for new_symbol in symbol_to_new_symbols.get(symbol).into_iter().flatten() {
+                    new_transitions
+                        .entry(*new_symbol)
+                        .or_default()
+                        .extend(next_state_set.iter().copied());
+                }
+            }
+        }
+
+        Ok(transition_function)
     }
 
     #[allow(dead_code)]
@@ -109,12 +143,10 @@ mod tests {
     #[test]
     fn check_normal_and_multiple_transitions() {
         let mut nt = NTransitionFunction::new();
-        let _ = nt
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
-        let _ = nt
-            .add_transition(&0, &Symbol::Character('a'), &2)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+        nt.add_transition(&0, &Symbol::Character('a'), &2)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         assert!(nt.f.contains_key(&0));
         assert!(nt.f[&0].contains_key(&Symbol::Character('a')));
@@ -126,9 +158,8 @@ mod tests {
     fn check_adding_epsilon_transition() {
         let mut nt = NTransitionFunction::new();
 
-        let _ = nt
-            .add_transition(&0, &Symbol::Epsilon, &2)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt.add_transition(&0, &Symbol::Epsilon, &2)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
         assert!(nt.f.contains_key(&0));
         assert!(nt.f[&0].contains_key(&Symbol::Epsilon));
         assert!(nt.f[&0][&Symbol::Epsilon].contains(&2));
@@ -137,9 +168,8 @@ mod tests {
     #[test]
     fn check_adding_same_transition() {
         let mut nt = NTransitionFunction::new();
-        let _ = nt
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
         let result = nt.add_transition(&0, &Symbol::Character('a'), &1);
 
         assert!(result.is_err_and(|err| err.to_string().contains("Existing Transition")));
@@ -148,29 +178,24 @@ mod tests {
     #[test]
     fn check_adding_transitions_from_another_function() {
         let mut nt1 = NTransitionFunction::new();
-        let _ = nt1
-            .add_transition(&0, &Symbol::Character('a'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt1.add_transition(&0, &Symbol::Character('a'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let mut nt2 = NTransitionFunction::new();
-        let _ = nt2
-            .add_transition(&0, &Symbol::Character('b'), &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt2.add_transition(&0, &Symbol::Character('b'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let mut nt3 = NTransitionFunction::new();
-        let _ = nt3
-            .add_transition(&1, &Symbol::Character('b'), &4)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt3.add_transition(&1, &Symbol::Character('b'), &4)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let mut nt4 = NTransitionFunction::new();
-        let _ = nt4
-            .add_transition(&5, &Symbol::Character('d'), &6)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt4.add_transition(&5, &Symbol::Character('d'), &6)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let mut nt5 = NTransitionFunction::new();
-        let _ = nt5
-            .add_transition(&0, &Symbol::Character('a'), &5)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+        nt5.add_transition(&0, &Symbol::Character('a'), &5)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         let nt = nt1.combine_transition(&nt2);
         let nt = nt.combine_transition(&nt3);
@@ -185,4 +210,33 @@ mod tests {
 
         assert!(nt.contains_transition(&5, &Symbol::Character('d'), &6));
     }
+
+    #[test]
+    fn check_remapping_to_finer_symbol_table() {
+        let mut from = SymbolTable::new();
+        from.add_range('a', 'z');
+        let mut to = from.clone();
+        to.add_character('m');
+
+        let mut nt = NTransitionFunction::new();
+        nt.add_transition(&0, &Symbol::Range('a', 'z'), &1)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+        nt.add_transition(&0, &Symbol::Epsilon, &2)
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+
+        // a-z is split into a-l, m and n-z, each keeping the transition
+        let nt = nt.remap(&from, &to).unwrap();
+        for symbol in [
+            Symbol::Range('a', 'l'),
+            Symbol::Character('m'),
+            Symbol::Range('n', 'z'),
+        ] {
+            assert!(nt.contains_transition(&0, &symbol, &1));
+        }
+        assert!(nt.contains_transition(&0, &Symbol::Epsilon, &2));
+        assert!(!nt.is_valid_transition(&0, &Symbol::Range('a', 'z')));
+
+        let result = nt.remap(&to, &from);
+        assert!(result.is_err_and(|err| err.to_string().contains("does not refine")));
+    }
 }
@@ -1,6 +1,7 @@
 use std::collections::{HashSet, VecDeque};
 
 use crate::{
+    custom_errors::AutomatonError,
     dfa::DFA,
     state::State,
     symbol_table::{Symbol, SymbolTable},
@@ -81,11 +82,39 @@ impl NFA {
 
         nfa.transition_function
             .add_transition(&0, symbol, &1)
-            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err.to_string()));
+            .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
 
         nfa
     }
 
+    /// creates an NFA which accepts any one of `symbols`
+    pub fn from_symbols(symbols: &[Symbol], symbol_table: &SymbolTable) -> NFA {
+        let mut nfa = NFA::empty(symbol_table);
+
+        for symbol in symbols {
+            nfa.transition_function
+                .add_transition(&0, symbol, &1)
+                .unwrap_or_else(|err| panic!("Error in adding transition : {}", err));
+        }
+
+        nfa
+    }
+
+    /// creates an NFA which accepts no string at all
+    pub fn empty(symbol_table: &SymbolTable) -> NFA {
+        // the final state is not reachable from the start state
+        NFA {
+            num_states: 2,
+            symbol_table: symbol_table.clone(),
+            states: HashSet::from([0, 1]),
+            begin_state_num: 0,
+            end_state_num: 1,
+            start_state: 0,
+            final_state: 1,
+            transition_function: NTransitionFunction::new(),
+        }
+    }
+
     /// extending by `increment`
     pub fn extend(&mut self, increment: usize) {
         for state in (self.begin_state_num..self.end_state_num + 1).rev() {
@@ -105,6 +134,18 @@ impl NFA {
         self.transition_function.extend(increment);
     }
 
+    /// returns the same NFA over `symbol_table`, which has to refine its own symbol table,
+    /// a transition on a symbol becomes one on every symbol of the new table inside it
+    pub fn with_symbol_table(&self, symbol_table: &SymbolTable) -> Result<NFA, AutomatonError> {
+        let mut nfa = self.clone();
+        nfa.transition_function = self
+            .transition_function
+            .remap(&self.symbol_table, symbol_table)?;
+        nfa.symbol_table = symbol_table.clone();
+
+        Ok(nfa)
+    }
+
     /// to check if a transition is valid, on a state and symbol
     pub fn is_valid_transition(&self, state: &State, symbol: &Symbol) -> bool {
         self.transition_function.is_valid_transition(state, symbol)
@@ -150,33 +191,22 @@ impl NFA {
 
     /// epsilon closure of a set of states
     pub fn epsilon_closure_of_set_of_states(&self, states: &HashSet<State>) -> HashSet<State> {
-        let mut ans = HashSet::new();
-        if states.len() == 0 {
-            return ans;
-        }
+        let mut ans = states.clone();
 
-        for &state in states.iter() {
-            ans.insert(state);
-        }
+        // a single search from all of the states, every state is visited once
+        let mut q: VecDeque<State> = states.iter().copied().collect();
 
-        loop {
-            let mut new_states = HashSet::new();
-            for &state in ans.iter() {
-                let eps_closure = self.epsilon_closure(&state);
-                for &state in eps_closure.iter() {
-                    if !ans.contains(&state) {
-                        new_states.insert(state);
+        while let Some(state) = q.pop_front() {
+            if let Some(next_states_on_epsilon) = self
+                .transition_function
+                .get_transition(&state, &Symbol::Epsilon)
+            {
+                for &next_state in next_states_on_epsilon.iter() {
+                    if ans.insert(next_state) {
+                        q.push_back(next_state);
                     }
                 }
             }
-
-            if new_states.len() == 0 {
-                break;
-            }
-
-            for state in new_states {
-                ans.insert(state);
-            }
         }
 
         ans
@@ -215,14 +245,57 @@ impl NFA {
 
         nfa
     }
+
+    /// creates an NFA accepting the reversal of the language of a DFA
+    pub fn reverse_of_dfa(dfa: &DFA) -> NFA {
+        // a new start state moves on epsilon to every final state of the dfa,
+        // and the start state of the dfa becomes the final state
+        let new_start_state = dfa.end_state_num() + 1;
+
+        let mut nfa = NFA {
+            num_states: dfa.num_states() + 1,
+            symbol_table: dfa.symbol_table().clone(),
+            states: dfa.states().clone(),
+            begin_state_num: dfa.begin_state_num(),
+            end_state_num: new_start_state,
+            start_state: new_start_state,
+            final_state: dfa.start_state(),
+            transition_function: NTransitionFunction::new(),
+        };
+        nfa.states.insert(new_start_state);
+
+        for curr_state in dfa.begin_state_num()..=dfa.end_state_num() {
+            for &symbol in dfa.symbol_table().symbols() {
+                if let Some(next_state) = dfa.get_transition(&curr_state, &symbol) {
+                    let _ =
+                        nfa.transition_function
+                            .add_transition(&next_state, &symbol, &curr_state);
+                }
+            }
+        }
+
+        for &final_state in dfa.final_states().iter() {
+            let _ = nfa.transition_function.add_transition(
+                &new_start_state,
+                &Symbol::Epsilon,
+                &final_state,
+            );
+        }
+
+        nfa
+    }
 }
 
 /// Functions to create an NFA from existing NFAs
 impl NFA {
-    /// returns NFA accepting union of 2 NFAs
-    pub fn union(mut self, mut other: NFA) -> NFA {
+    /// returns NFA accepting union of 2 NFAs, the symbol tables are merged if they differ
+    pub fn union(mut self, mut other: NFA) -> Result<NFA, AutomatonError> {
         if self.symbol_table != other.symbol_table {
-            panic!("Symbol table of 2 NFAs are not the same");
+            let symbol_table = self.symbol_table.merge(&other.symbol_table);
+
+            return self
+                .with_symbol_table(&symbol_table)?
+                .union(other.with_symbol_table(&symbol_table)?);
         }
         let x = self.num_states();
         let y = other.num_states();
@@ -246,9 +319,9 @@ impl NFA {
         nfa.states.insert(x + y + 1);
 
         // add states of self
-        let union: HashSet<_> = nfa.states.union(&self.states).map(|&state| state).collect();
+        let union: HashSet<_> = nfa.states.union(&self.states).copied().collect();
         // add states of other
-        let union: HashSet<_> = union.union(&other.states).map(|&state| state).collect();
+        let union: HashSet<_> = union.union(&other.states).copied().collect();
 
         // set nfa.states to union
         nfa.states = union;
@@ -272,13 +345,18 @@ impl NFA {
             .transition_function
             .add_transition(&(x + y), &epsilon, &(x + y + 1));
 
-        nfa
+        Ok(nfa)
     }
 
-    /// function to create an NFA accepting concatenation of 2 languages
-    pub fn concat(mut self, mut other: NFA) -> NFA {
+    /// function to create an NFA accepting concatenation of 2 languages,
+    /// the symbol tables are merged if they differ
+    pub fn concat(mut self, mut other: NFA) -> Result<NFA, AutomatonError> {
         if self.symbol_table != other.symbol_table {
-            panic!("Symbol table of 2 NFAs are not the same");
+            let symbol_table = self.symbol_table.merge(&other.symbol_table);
+
+            return self
+                .with_symbol_table(&symbol_table)?
+                .concat(other.with_symbol_table(&symbol_table)?);
         }
 
         let x = self.num_states();
@@ -300,9 +378,9 @@ impl NFA {
         nfa.states.insert(0);
         // final_state is same as the final_state of the second NFA
         // add states of self
-        let union: HashSet<_> = nfa.states.union(&self.states).map(|&state| state).collect();
+        let union: HashSet<_> = nfa.states.union(&self.states).copied().collect();
         // add states of other
-        let union: HashSet<_> = union.union(&other.states).map(|&state| state).collect();
+        let union: HashSet<_> = union.union(&other.states).copied().collect();
 
         let start_state_of_first = self.start_state();
         let final_state_of_first = self.final_state();
@@ -332,7 +410,7 @@ impl NFA {
             &start_state_of_second,
         );
 
-        nfa
+        Ok(nfa)
     }
 
     /// function to create NFA to accept Kleene star of a language
@@ -359,7 +437,7 @@ impl NFA {
         let start_state_of_first = self.start_state();
         let final_state_of_first = self.final_state();
 
-        let union: HashSet<_> = nfa.states.union(&self.states).map(|&state| state).collect();
+        let union: HashSet<_> = nfa.states.union(&self.states).copied().collect();
         // set nfa.states to union
         nfa.states = union;
 
@@ -395,6 +473,66 @@ impl NFA {
 
         nfa
     }
+
+    /// function to create an NFA accepting between `min` and `max` strings of the language,
+    /// at least `min` of them if there is no `max`
+    ///
+    /// the copies are laid out one after the other instead of being concatenated, which
+    /// would renumber the NFA built so far for every copy
+    pub fn repeat(self, min: usize, max: Option<usize>) -> NFA {
+        let x = self.num_states();
+        // without a bound the last copy loops
+        let num_copies = max.map_or(min + 1, |max| max.max(min));
+
+        let final_state = num_copies * x + 1;
+        let mut nfa = NFA {
+            num_states: num_copies * x + 2,
+            symbol_table: self.symbol_table.clone(),
+            states: HashSet::from([0, final_state]),
+            begin_state_num: 0,
+            end_state_num: final_state,
+            start_state: 0,
+            final_state,
+            transition_function: NTransitionFunction::new(),
+        };
+
+        let epsilon = Symbol::Epsilon;
+        // the state reached after the copies so far
+        let mut entry = nfa.start_state();
+
+        for copy_num in 0..num_copies {
+            if copy_num >= min {
+                let _ = nfa
+                    .transition_function
+                    .add_transition(&entry, &epsilon, &final_state);
+            }
+
+            let mut copy = self.clone();
+            copy.extend(copy_num * x + 1 - copy.begin_state_num);
+
+            nfa.states.extend(copy.states.iter().copied());
+            nfa.transition_function = nfa
+                .transition_function
+                .combine_transition(&copy.transition_function);
+
+            let _ = nfa
+                .transition_function
+                .add_transition(&entry, &epsilon, &copy.start_state());
+            entry = copy.final_state();
+
+            if max.is_none() && copy_num == num_copies - 1 {
+                let _ =
+                    nfa.transition_function
+                        .add_transition(&entry, &epsilon, &copy.start_state());
+            }
+        }
+
+        let _ = nfa
+            .transition_function
+            .add_transition(&entry, &epsilon, &final_state);
+
+        nfa
+    }
 }
 
 #[cfg(test)]
@@ -431,7 +569,7 @@ mod tests {
         let nfa1 = NFA::from_symbol(&a, &symbol_table);
         let nfa2 = NFA::from_symbol(&b, &symbol_table);
 
-        let nfa_union = nfa1.union(nfa2);
+        let nfa_union = nfa1.union(nfa2).unwrap();
 
         assert!(nfa_union.contains_transition(&0, &epsilon, &1));
         assert!(nfa_union.contains_transition(&0, &epsilon, &3));
@@ -461,7 +599,7 @@ mod tests {
         let nfa1 = NFA::from_symbol(&a, &symbol_table);
         let nfa2 = NFA::from_symbol(&b, &symbol_table);
 
-        let nfa_union = nfa1.union(nfa2);
+        let nfa_union = nfa1.union(nfa2).unwrap();
         let epsilon_closure_check = nfa_union.epsilon_closure(&0);
 
         assert!(epsilon_closure_check.len() == 3);
@@ -491,7 +629,7 @@ mod tests {
         let nfa1 = NFA::from_symbol(&a, &symbol_table);
         let nfa2 = NFA::from_symbol(&b, &symbol_table);
 
-        let nfa_union = nfa1.union(nfa2);
+        let nfa_union = nfa1.union(nfa2).unwrap();
         let dfa = DFA::convert_to_dfa(nfa_union);
 
         let result = dfa.run("a");
@@ -535,7 +673,7 @@ mod tests {
         let nfa1 = NFA::from_symbol(&a, &symbol_table);
         let nfa2 = NFA::from_symbol(&b, &symbol_table);
 
-        let nfa_concat = nfa1.concat(nfa2);
+        let nfa_concat = nfa1.concat(nfa2).unwrap();
         let dfa = DFA::convert_to_dfa(nfa_concat);
 
         let result = dfa.run("ab");
@@ -576,6 +714,51 @@ mod tests {
         assert!(result.is_ok_and(|res| !res));
     }
 
+    #[test]
+    fn check_repetition() {
+        let mut symbol_table = SymbolTable::new();
+        symbol_table.add_character('a');
+        symbol_table.add_character('b');
+
+        let ab = || {
+            NFA::from_symbol(&Symbol::Character('a'), &symbol_table)
+                .concat(NFA::from_symbol(&Symbol::Character('b'), &symbol_table))
+                .unwrap()
+        };
+
+        for (min, max, accepted) in [
+            (0, Some(0), vec![0]),
+            (1, Some(3), vec![1, 2, 3]),
+            (2, None, vec![2, 3, 4, 5]),
+            (0, None, vec![0, 1, 2, 3, 4, 5]),
+        ] {
+            let nfa = ab().repeat(min, max);
+            assert_eq!(
+                nfa.num_states(),
+                ab().num_states() * max.unwrap_or(min + 1) + 2
+            );
+
+            let dfa = DFA::convert_to_dfa(nfa);
+            for count in 0..=5 {
+                let result = dfa.run(&"ab".repeat(count));
+                assert_eq!(
+                    result.unwrap(),
+                    accepted.contains(&count),
+                    "{} {:?}",
+                    min,
+                    max
+                );
+            }
+            assert!(dfa.run("aba").is_ok_and(|res| !res));
+        }
+
+        // the copies of the empty string
+        let nfa = NFA::from_symbol(&Symbol::Epsilon, &symbol_table).repeat(2, None);
+        let dfa = DFA::convert_to_dfa(nfa);
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+    }
+
     #[test]
     fn check_sample_regular_expression() {
         // check for (a + b)*c
@@ -592,10 +775,10 @@ mod tests {
         let nfa_b = NFA::from_symbol(&b, &symbol_table);
         let nfa_c = NFA::from_symbol(&c, &symbol_table);
 
-        let nfa_a_plus_b = nfa_a.union(nfa_b);
+        let nfa_a_plus_b = nfa_a.union(nfa_b).unwrap();
         let nfa_a_plus_b_kleene_star = nfa_a_plus_b.kleene_star();
 
-        let nfa = nfa_a_plus_b_kleene_star.concat(nfa_c);
+        let nfa = nfa_a_plus_b_kleene_star.concat(nfa_c).unwrap();
 
         let dfa = DFA::convert_to_dfa(nfa);
 
@@ -625,14 +808,14 @@ mod tests {
         let nfa_b = NFA::from_symbol(&b, &symbol_table);
         let nfa_c = NFA::from_symbol(&c, &symbol_table);
 
-        let nfa_a_plus_b = nfa_a.union(nfa_b);
+        let nfa_a_plus_b = nfa_a.union(nfa_b).unwrap();
         let nfa_a_plus_b_kleene_star = nfa_a_plus_b.kleene_star();
 
-        let nfa = nfa_a_plus_b_kleene_star.concat(nfa_c);
+        let nfa = nfa_a_plus_b_kleene_star.concat(nfa_c).unwrap();
 
         let dfa1 = DFA::convert_to_dfa(nfa);
         let dfa2 = DFA::from_string("abc", &symbol_table);
-        let dfa = dfa1.intersection(dfa2);
+        let dfa = dfa1.intersection(dfa2).unwrap();
 
         let result = dfa.run("abc");
         assert!(result.is_ok_and(|res| res));
@@ -657,19 +840,19 @@ mod tests {
         let nfa_b = NFA::from_symbol(&b, &symbol_table);
         let nfa_c = NFA::from_symbol(&c, &symbol_table);
 
-        let nfa_a_plus_b = nfa_a.union(nfa_b.clone());
+        let nfa_a_plus_b = nfa_a.union(nfa_b.clone()).unwrap();
         let nfa_a_plus_b_kleene_star = nfa_a_plus_b.kleene_star();
 
-        let nfa1 = nfa_a_plus_b_kleene_star.concat(nfa_c.clone());
+        let nfa1 = nfa_a_plus_b_kleene_star.concat(nfa_c.clone()).unwrap();
 
         let nfa_b_kleene_star = nfa_b.kleene_star();
-        let nfa2 = nfa_b_kleene_star.concat(nfa_c);
+        let nfa2 = nfa_b_kleene_star.concat(nfa_c).unwrap();
 
         let dfa1 = DFA::convert_to_dfa(nfa1);
         let dfa2 = DFA::convert_to_dfa(nfa2);
 
         // this should accept b*c
-        let dfa = dfa1.intersection(dfa2);
+        let dfa = dfa1.intersection(dfa2).unwrap();
         let result = dfa.run("bc");
         assert!(result.is_ok_and(|res| res));
 
@@ -690,4 +873,32 @@ mod tests {
             assert!(result.is_ok_and(|res| res));
         }
     }
+
+    #[test]
+    fn check_combining_nfas_over_different_symbol_tables() {
+        let mut first_table = SymbolTable::new();
+        first_table.add_range('a', 'z');
+        let mut second_table = SymbolTable::new();
+        second_table.add_character('m');
+        second_table.add_character('0');
+
+        // [a-z] and m0
+        let letter = NFA::from_symbol(&Symbol::Range('a', 'z'), &first_table);
+        let m0 = NFA::from_symbol(&Symbol::Character('m'), &second_table)
+            .concat(NFA::from_symbol(&Symbol::Character('0'), &second_table))
+            .unwrap();
+
+        let nfa = letter.clone().union(m0.clone()).unwrap();
+        assert_eq!(nfa.symbol_table(), &first_table.merge(&second_table));
+
+        let dfa = DFA::convert_to_dfa(nfa);
+        for (input, expected) in [("q", true), ("m", true), ("m0", true), ("0", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        let dfa = DFA::convert_to_dfa(letter.concat(m0).unwrap());
+        for (input, expected) in [("am0", true), ("mm0", true), ("m0", false), ("a0", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+    }
 }
@@ -1,180 +1,470 @@
-use std::collections::HashSet;
+//! This module contains the parser for reg-ex written in the prefix syntax
+//!
+//! ```text
+//! R -> concat(R, R)
+//! R -> union(R, R)
+//! R -> inter(R, R)  strings matched by both
+//! R -> diff(R, R)   strings matched by the first but not by the second
+//! R -> not(R)       strings not matched
+//! R -> star(R)
+//! R -> plus(R)      one or more, same as concat(R,star(R))
+//! R -> opt(R)       zero or one, same as union(R,eps)
+//! R -> repeat(R, m, n)
+//!                   at least m and at most n times, without n there is no maximum
+//! R -> symbol(C)    `\` escapes are `\n`, `\t`, `\r`, `\0`, `\u{hex}`, any other
+//!                   escaped character like `\)` or `\\` stands for itself
+//! R -> class(I)     a character class like `class(a-z_)`, `class(^0-9)` is its complement
+//! R -> any()        any single character
+//! R -> eps()        the empty string
+//! R -> empty()      the empty language, matching nothing
+//! ```
+
+use std::fmt::{self, Display, Formatter};
 
 use thiserror::Error;
 
 use crate::{
     dfa::DFA,
     nfa::NFA,
-    symbol_table::{Symbol, SymbolTable},
+    regex::{RegexError, DEFAULT_SIZE_LIMIT},
+    regex_ast::RegexAst,
 };
 
-type Stack<T> = Vec<T>;
+/// number of characters shown on each side of the error position in a snippet
+const SNIPPET_CONTEXT: usize = 30;
+
+/// deepest nesting of operators the parsers accept, the parsers and the functions
+/// walking the syntax tree are recursive and must not overflow the stack
+pub const MAX_NESTING_DEPTH: usize = 200;
+
+/// Error in a reg-ex, with the position at which the parser got stuck
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("unexpected {} at offset {position}, expected {expected}", Found(*.found))]
+pub struct ParsingError {
+    /// byte offset of the error in the reg-ex
+    pub position: usize,
+    /// the character at `position`, `None` at the end of the reg-ex
+    pub found: Option<char>,
+    /// what the parser was looking for
+    pub expected: String,
+    /// the whole reg-ex, to render the snippet
+    input: String,
+}
+
+/// prints the offending character of an error
+struct Found(Option<char>);
 
-#[derive(Clone, Debug, Error)]
-pub enum ParsingError {
-    #[error("Parsing Error")]
-    ParseError,
+impl Display for Found {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(ch) => write!(f, "`{}`", ch.escape_debug()),
+            None => write!(f, "end of input"),
+        }
+    }
 }
 
-/// creating an NFA from reg-ex
-pub fn create_nfa_from_reg_ex(input: &str) -> Result<NFA, ParsingError> {
-    let symbol_table = create_symbol_table(input)?;
+impl ParsingError {
+    pub fn new(input: &str, position: usize, expected: impl Into<String>) -> ParsingError {
+        ParsingError {
+            position,
+            found: input[position..].chars().next(),
+            expected: expected.into(),
+            input: input.to_string(),
+        }
+    }
 
-    let bytes = input.as_bytes();
+    /// renders the line of the reg-ex around the error with a caret under it
+    ///
+    /// ```text
+    /// concat(symbol(a)symbol(b))
+    ///                 ^
+    /// ```
+    pub fn snippet(&self) -> String {
+        let before: Vec<char> = self.input[..self.position].chars().collect();
+        let after: Vec<char> = self.input[self.position..].chars().collect();
+
+        let mut line = String::new();
+        let mut caret_column = before.len();
+
+        if before.len() > SNIPPET_CONTEXT {
+            line.push_str("...");
+            line.extend(&before[before.len() - SNIPPET_CONTEXT..]);
+            caret_column = SNIPPET_CONTEXT + 3;
+        } else {
+            line.extend(&before);
+        }
 
-    let mut string_stack: Stack<&str> = Stack::new();
-    let mut nfa_stack: Stack<NFA> = Stack::new();
+        if after.len() > SNIPPET_CONTEXT {
+            line.extend(&after[..SNIPPET_CONTEXT]);
+            line.push_str("...");
+        } else {
+            line.extend(&after);
+        }
 
-    let mut i = 0;
-    let n = bytes.len();
-    while i < n {
-        if bytes[i] == b'c' {
-            // has to start with concat
-            if i + 7 >= n {
-                return Err(ParsingError::ParseError);
-            }
-            if &input[i..i + 7] == "concat(" {
-                string_stack.push("(");
-                string_stack.push("concat");
-                i += 7;
-            } else {
-                return Err(ParsingError::ParseError);
-            }
-        } else if bytes[i] == b'u' {
-            // has to be union
-            if i + 6 >= n {
-                return Err(ParsingError::ParseError);
-            }
+        format!("{}\n{}^", line, " ".repeat(caret_column))
+    }
+}
 
-            if &input[i..i + 6] == "union(" {
-                string_stack.push("(");
-                string_stack.push("union");
-                i += 6;
-            } else {
-                return Err(ParsingError::ParseError);
-            }
-        } else if bytes[i] == b's' {
-            // must be star or symbol
-            if i + 5 >= n {
-                return Err(ParsingError::ParseError);
-            }
+/// Position in a reg-ex being parsed, shared by the parsers of both syntaxes
+pub(crate) struct Cursor<'a> {
+    input: &'a str,
+    // (byte offset, character)
+    chars: Vec<(usize, char)>,
+    position: usize,
+    // number of operators the current operand is nested in
+    depth: usize,
+}
+
+impl Cursor<'_> {
+    pub fn new(input: &str) -> Cursor<'_> {
+        Cursor {
+            input,
+            chars: input.char_indices().collect(),
+            position: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.peek_nth(0)
+    }
+
+    /// returns the `n`th character after the current one
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.get(self.position + n).map(|&(_, ch)| ch)
+    }
+
+    /// consumes the current character
+    pub fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.position += 1;
+
+        Some(ch)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.chars.len()
+    }
+
+    /// byte offset of the current character
+    pub fn offset(&self) -> usize {
+        self.chars
+            .get(self.position)
+            .map(|&(offset, _)| offset)
+            .unwrap_or(self.input.len())
+    }
+
+    /// goes one operator deeper, failing at the current character once the operands
+    /// are nested more than `MAX_NESTING_DEPTH` deep
+    pub fn enter(&mut self) -> Result<(), ParsingError> {
+        if self.depth == MAX_NESTING_DEPTH {
+            return Err(self.error(format!("at most {} nested operators", MAX_NESTING_DEPTH)));
+        }
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    /// goes back out of `levels` operators
+    pub fn leave(&mut self, levels: usize) {
+        self.depth -= levels;
+    }
+
+    /// error at the current character
+    pub fn error(&self, expected: impl Into<String>) -> ParsingError {
+        ParsingError::new(self.input, self.offset(), expected)
+    }
 
-            if &input[i..i + 5] == "star(" {
-                string_stack.push("(");
-                string_stack.push("star");
-                i += 5;
-            } else if i + 7 >= n {
-                return Err(ParsingError::ParseError);
-            } else if &input[i..i + 7] == "symbol(" && bytes[i + 8] == b')' {
-                // since its a symbol it will be only a single character
-                // skip by length of symbol(a)
-                let nfa_from_symbol =
-                    NFA::from_symbol(&Symbol::Character(bytes[i + 7] as char), &symbol_table);
-                nfa_stack.push(nfa_from_symbol);
-
-                i += 9;
+    /// error at an earlier byte offset
+    pub fn error_at(&self, offset: usize, expected: impl Into<String>) -> ParsingError {
+        ParsingError::new(self.input, offset, expected)
+    }
+
+    /// consumes `ch` or fails with what was `expected`
+    pub fn expect(&mut self, ch: char, expected: &str) -> Result<(), ParsingError> {
+        if self.peek() != Some(ch) {
+            return Err(self.error(expected));
+        }
+        self.position += 1;
+
+        Ok(())
+    }
+
+    /// parses a repetition count, `None` if there are no digits
+    pub fn parse_count(&mut self) -> Result<Option<u32>, ParsingError> {
+        let offset = self.offset();
+        let mut count: Option<u32> = None;
+
+        while let Some(digit) = self.peek().and_then(|ch| ch.to_digit(10)) {
+            count = Some(
+                count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit))
+                    .ok_or_else(|| self.error_at(offset, "a count of at most 4294967295"))?,
+            );
+            self.bump();
+        }
+
+        Ok(count)
+    }
+
+    /// parses the inside of a character class up to and including `close`,
+    /// returning its ranges and whether it is negated
+    ///
+    /// ```text
+    /// class := '^'? item* close
+    /// item  := C ('-' C)?       `\` escapes any of `-^])\`, a `-` without a
+    ///                           character on both sides is taken literally
+    /// ```
+    pub fn parse_class(&mut self, close: char) -> Result<(Vec<(char, char)>, bool), ParsingError> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.bump();
+        }
+
+        let mut ranges = Vec::new();
+
+        while self.peek() != Some(close) {
+            let lo = self.parse_class_char(close)?;
+
+            let is_range =
+                self.peek() == Some('-') && self.peek_nth(1).is_some_and(|next| next != close);
+
+            if is_range {
+                self.bump();
+                let hi_offset = self.offset();
+                let hi = self.parse_class_char(close)?;
+
+                if hi < lo {
+                    return Err(self.error_at(
+                        hi_offset,
+                        format!("a character not before `{}` to end the range", lo),
+                    ));
+                }
+                ranges.push((lo, hi));
             } else {
-                return Err(ParsingError::ParseError);
+                ranges.push((lo, lo));
             }
-        } else if bytes[i] == b')' {
-            i += 1;
-
-            while let Some(string) = string_stack.pop() {
-                match string {
-                    "star" => {
-                        if let Some(nfa) = nfa_stack.pop() {
-                            // push kleene star onto stack
-                            let nfa_kleene_star = nfa.kleene_star();
-                            nfa_stack.push(nfa_kleene_star);
-                        } else {
-                            return Err(ParsingError::ParseError);
-                        }
-                    }
-                    "union" => {
-                        if nfa_stack.len() < 2 {
-                            return Err(ParsingError::ParseError);
-                        }
-                        let second_nfa = nfa_stack.pop().unwrap();
-                        let first_nfa = nfa_stack.pop().unwrap();
-                        let nfa_union = first_nfa.union(second_nfa);
-
-                        nfa_stack.push(nfa_union);
-                    }
-                    "concat" => {
-                        if nfa_stack.len() < 2 {
-                            return Err(ParsingError::ParseError);
-                        }
-                        let second_nfa = nfa_stack.pop().unwrap();
-                        let first_nfa = nfa_stack.pop().unwrap();
-                        let nfa_concat = first_nfa.concat(second_nfa);
-
-                        nfa_stack.push(nfa_concat);
-                    }
-                    "(" => {
+        }
+        self.bump();
+
+        Ok((ranges, negated))
+    }
+
+    /// a possibly escaped character of a class
+    fn parse_class_char(&mut self, close: char) -> Result<char, ParsingError> {
+        self.parse_char(&format!("`{}` to close the character class", close))
+    }
+
+    /// reads a character, where `\` starts an escape sequence
+    ///
+    /// `\n`, `\t`, `\r` and `\0` are the control characters, `\u{1F600}` is the character
+    /// with that hexadecimal code point and any other escaped character stands for itself
+    pub fn parse_char(&mut self, expected: &str) -> Result<char, ParsingError> {
+        match self.bump() {
+            Some('\\') => self.parse_escape(),
+            Some(ch) => Ok(ch),
+            None => Err(self.error(expected)),
+        }
+    }
+
+    /// the rest of an escape sequence, after its `\`
+    fn parse_escape(&mut self) -> Result<char, ParsingError> {
+        match self.bump() {
+            Some('n') => Ok('\n'),
+            Some('t') => Ok('\t'),
+            Some('r') => Ok('\r'),
+            Some('0') => Ok('\0'),
+            Some('u') => {
+                self.expect('{', "`{` after `\\u`")?;
+
+                let digits_offset = self.offset();
+                let mut code_point: u32 = 0;
+                let mut num_digits = 0;
+
+                // a code point has at most 6 hexadecimal digits
+                while let Some(digit) = self.peek().and_then(|ch| ch.to_digit(16)) {
+                    if num_digits == 6 {
                         break;
                     }
-                    _ => {
-                        return Err(ParsingError::ParseError);
-                    }
+                    code_point = code_point * 16 + digit;
+                    num_digits += 1;
+                    self.bump();
+                }
+
+                if num_digits == 0 {
+                    return Err(self.error("a hexadecimal code point after `\\u{`"));
                 }
+                self.expect('}', "`}` after at most 6 hexadecimal digits")?;
+
+                char::from_u32(code_point).ok_or_else(|| {
+                    self.error_at(digits_offset, "a code point which is not a surrogate")
+                })
             }
-        } else if bytes[i] == b',' {
-            // comma is just a separator
-            i += 1;
-        } else {
-            return Err(ParsingError::ParseError);
+            Some(ch) => Ok(ch),
+            None => Err(self.error("a character after `\\`")),
         }
     }
+}
+
+/// parsing a reg-ex into its syntax tree
+pub fn parse_reg_ex(input: &str) -> Result<RegexAst, ParsingError> {
+    let mut parser = PrefixParser {
+        cursor: Cursor::new(input),
+    };
 
-    if nfa_stack.len() != 1 {
-        return Err(ParsingError::ParseError);
+    let ast = parser.parse_reg_ex()?;
+
+    if !parser.cursor.is_at_end() {
+        return Err(parser.cursor.error("end of input after the reg-ex"));
     }
 
-    Ok(nfa_stack.pop().unwrap())
+    Ok(ast)
 }
 
-/// creating a DFA from reg-ex
-pub fn create_dfa_from_reg_ex(input: &str) -> Result<DFA, ParsingError> {
-    let nfa = create_nfa_from_reg_ex(input)?;
-    let dfa = DFA::convert_to_dfa(nfa);
-    let dfa = dfa.minimized_dfa();
+/// creating an NFA from reg-ex, failing if it needs more than `DEFAULT_SIZE_LIMIT` states
+pub fn create_nfa_from_reg_ex(input: &str) -> Result<NFA, RegexError> {
+    parse_reg_ex(input)?
+        .to_nfa_with_size_limit(DEFAULT_SIZE_LIMIT)
+        .ok_or(RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        })
+}
 
-    Ok(dfa)
+/// creating a DFA from reg-ex, failing if it needs more than `DEFAULT_SIZE_LIMIT` states
+pub fn create_dfa_from_reg_ex(input: &str) -> Result<DFA, RegexError> {
+    parse_reg_ex(input)?
+        .to_dfa_with_size_limit(DEFAULT_SIZE_LIMIT)
+        .ok_or(RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        })
 }
 
-/// function to extract the symbols from the input string
-fn extract_symbols(input: &str) -> Result<HashSet<char>, ParsingError> {
-    let mut result = HashSet::new();
-    let bytes = input.as_bytes();
+/// names of the operators, each followed by its parenthesised operands
+const OPERATOR_NAMES: [&str; 14] = [
+    "concat", "union", "inter", "diff", "not", "star", "plus", "opt", "repeat", "symbol", "class",
+    "any", "eps", "empty",
+];
 
-    let mut i = 0;
-    while i + 8 < bytes.len() {
-        if &input[i..i + 7] == "symbol(" && bytes[i + 8] != b')' {
-            return Err(ParsingError::ParseError);
-        }
-        if &input[i..i + 7] == "symbol(" && bytes[i + 8] == b')' {
-            // The character at position i+7 is the one inside symbol(...)
-            result.insert(input.chars().nth(i + 7).unwrap());
-            i += 9; // move past "symbol(x)"
-        } else {
-            i += 1;
+/// recursive descent parser, every operator is a name followed by its parenthesised operands
+struct PrefixParser<'a> {
+    cursor: Cursor<'a>,
+}
+
+impl PrefixParser<'_> {
+    /// reads the operator name in front of `(`
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+
+        while let Some(ch) = self.cursor.peek() {
+            if !ch.is_ascii_lowercase() {
+                break;
+            }
+            name.push(ch);
+            self.cursor.bump();
         }
+
+        name
     }
 
-    Ok(result)
-}
+    fn parse_reg_ex(&mut self) -> Result<RegexAst, ParsingError> {
+        self.cursor.enter()?;
+        let name_offset = self.cursor.offset();
+        let name = self.parse_name();
 
-/// function to create a symbol table after extracting the symbols from the input reg ex
-fn create_symbol_table(input: &str) -> Result<SymbolTable, ParsingError> {
-    let symbols = extract_symbols(input)?;
+        if !OPERATOR_NAMES.contains(&name.as_str()) {
+            let names: Vec<String> = OPERATOR_NAMES
+                .iter()
+                .map(|name| format!("`{}(`", name))
+                .collect();
 
-    let mut symbol_table = SymbolTable::new();
+            return Err(self
+                .cursor
+                .error_at(name_offset, format!("one of {}", names.join(", "))));
+        }
 
-    for character in symbols {
-        symbol_table.add_character(character);
-    }
+        self.cursor.expect('(', &format!("`(` after `{}`", name))?;
+
+        let ast = match name.as_str() {
+            "concat" | "union" | "inter" | "diff" => {
+                let first = self.parse_reg_ex()?;
+                self.cursor
+                    .expect(',', &format!("`,` after first operand of {}", name))?;
+                let second = self.parse_reg_ex()?;
+                self.cursor
+                    .expect(')', &format!("`)` after second operand of {}", name))?;
+
+                match name.as_str() {
+                    "concat" => RegexAst::concat(first, second),
+                    "union" => RegexAst::union(first, second),
+                    "inter" => RegexAst::intersection(first, second),
+                    _ => RegexAst::difference(first, second),
+                }
+            }
+            "not" | "star" | "plus" | "opt" => {
+                let inner = self.parse_reg_ex()?;
+                self.cursor
+                    .expect(')', &format!("`)` after operand of {}", name))?;
+
+                match name.as_str() {
+                    "not" => RegexAst::complement(inner),
+                    "star" => RegexAst::star(inner),
+                    "plus" => RegexAst::plus(inner),
+                    _ => RegexAst::optional(inner),
+                }
+            }
+            "repeat" => {
+                let inner = self.parse_reg_ex()?;
+                self.cursor.expect(',', "`,` after operand of repeat")?;
+
+                let min = self
+                    .cursor
+                    .parse_count()?
+                    .ok_or_else(|| self.cursor.error("the minimum count of repeat"))?;
+                self.cursor
+                    .expect(',', "`,` after the minimum count of repeat")?;
+
+                let max_offset = self.cursor.offset();
+                let max = self.cursor.parse_count()?;
+                if max.is_some_and(|max| max < min) {
+                    return Err(self.cursor.error_at(
+                        max_offset,
+                        format!("a maximum count not below the minimum {}", min),
+                    ));
+                }
+                self.cursor
+                    .expect(')', "`)` after the maximum count of repeat")?;
+
+                RegexAst::repeat(inner, min, max)
+            }
+            "class" => {
+                let (ranges, negated) = self.cursor.parse_class(')')?;
+
+                RegexAst::class(ranges, negated)
+            }
+            "any" | "eps" | "empty" => {
+                self.cursor.expect(')', &format!("`)` after `{}(`", name))?;
+
+                match name.as_str() {
+                    "any" => RegexAst::Any,
+                    "eps" => RegexAst::Epsilon,
+                    _ => RegexAst::Empty,
+                }
+            }
+            _ => {
+                // since its a symbol it will be only a single, possibly escaped, character
+                let ch = self.cursor.parse_char("a character inside symbol(...)")?;
+                self.cursor
+                    .expect(')', "`)` after the single character of symbol")?;
+
+                RegexAst::Symbol(ch)
+            }
+        };
+        self.cursor.leave(1);
 
-    Ok(symbol_table)
+        Ok(ast)
+    }
 }
 
 #[cfg(test)]
@@ -186,7 +476,7 @@ mod tests {
     #[test]
     fn check_extracting_symbols() {
         let input = "concat(concat(symbol(a),symbol(1)),star(union(symbol(0),symbol(1))))";
-        let symbols = extract_symbols(input).unwrap();
+        let symbols = parse_reg_ex(input).unwrap().symbols();
         assert!(symbols.contains(&'a'));
         assert!(symbols.contains(&'0'));
         assert!(symbols.contains(&'1'));
@@ -212,4 +502,330 @@ mod tests {
         let result = dfa.run("010011");
         assert!(result.is_ok_and(|res| res));
     }
+
+    #[test]
+    fn check_input_outside_alphabet() {
+        let dfa = create_dfa_from_reg_ex("star(symbol(a))").unwrap();
+        assert!(dfa.run("aaxa").is_ok_and(|res| !res));
+
+        let dfa =
+            create_dfa_from_reg_ex("concat(star(symbol(a)),union(symbol(b),symbol(c)))").unwrap();
+        assert!(dfa.run("aad").is_ok_and(|res| !res));
+        assert!(dfa.complement().run("aad").is_ok_and(|res| res));
+        assert!(dfa.complement().run("aab").is_ok_and(|res| !res));
+    }
+
+    #[test]
+    fn check_character_classes() {
+        let dfa = create_dfa_from_reg_ex("concat(class(a-z_),star(class(a-z0-9_)))").unwrap();
+        assert!(dfa.run("snake_case_2").is_ok_and(|res| res));
+        assert!(dfa.run("_x").is_ok_and(|res| res));
+        assert!(dfa.run("2x").is_ok_and(|res| !res));
+        assert!(dfa.run("camelCase").is_ok_and(|res| !res));
+
+        // a negated class also matches characters which appear nowhere in the reg-ex
+        let dfa = create_dfa_from_reg_ex("concat(symbol(a),class(^a-c))").unwrap();
+        assert!(dfa.run("ad").is_ok_and(|res| res));
+        assert!(dfa.run("aé").is_ok_and(|res| res));
+        assert!(dfa.run("ab").is_ok_and(|res| !res));
+        assert!(dfa.run("aa").is_ok_and(|res| !res));
+
+        // `-` is literal where it cannot form a range
+        let ast = parse_reg_ex("class(-a-)").unwrap();
+        assert_eq!(
+            ast,
+            RegexAst::Class {
+                ranges: vec![('-', '-'), ('a', 'a'), ('-', '-')],
+                negated: false,
+            }
+        );
+
+        assert_eq!(parse_reg_ex("class()").unwrap(), RegexAst::Empty);
+
+        let err = parse_reg_ex("class(z-a)").unwrap_err();
+        assert_eq!(err.position, 8);
+        assert_eq!(err.expected, "a character not before `z` to end the range");
+
+        let err = parse_reg_ex("class(a-z").unwrap_err();
+        assert_eq!(err.position, 9);
+        assert_eq!(err.expected, "`)` to close the character class");
+    }
+
+    #[test]
+    fn check_any() {
+        // the alphabet is only `x`, still any other character is matched by any()
+        let dfa = create_dfa_from_reg_ex("concat(star(any()),symbol(x))").unwrap();
+        assert!(dfa.run("x").is_ok_and(|res| res));
+        assert!(dfa.run("abc ✓ x").is_ok_and(|res| res));
+        assert!(dfa.run("xa").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_reg_ex("concat(any(),any())").unwrap();
+        assert!(dfa.run("é!").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+
+        let err = parse_reg_ex("any(symbol(a))").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.expected, "`)` after `any(`");
+    }
+
+    #[test]
+    fn check_repetitions() {
+        let dfa = create_dfa_from_reg_ex("concat(plus(symbol(a)),opt(symbol(b)))").unwrap();
+        for (input, expected) in [("a", true), ("aaab", true), ("b", false), ("abb", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        let dfa = create_dfa_from_reg_ex("repeat(class(0-9),2,3)").unwrap();
+        for (input, expected) in [("1", false), ("12", true), ("123", true), ("1234", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        // without a maximum the repetitions are unbounded
+        let dfa = create_dfa_from_reg_ex("repeat(symbol(a),2,)").unwrap();
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+        assert!(dfa.run(&"a".repeat(50)).is_ok_and(|res| res));
+
+        let err = parse_reg_ex("repeat(symbol(a),3,2)").unwrap_err();
+        assert_eq!(err.position, 19);
+        assert_eq!(err.expected, "a maximum count not below the minimum 3");
+    }
+
+    #[test]
+    fn check_epsilon_and_empty() {
+        // a or nothing
+        let dfa = create_dfa_from_reg_ex("union(symbol(a),eps())").unwrap();
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| res));
+        assert!(dfa.run("aa").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_reg_ex("empty()").unwrap();
+        assert!(dfa.final_states().is_empty());
+        assert!(dfa.run("").is_ok_and(|res| !res));
+        assert!(dfa.complement().run("anything").is_ok_and(|res| res));
+
+        // concatenating with the empty language leaves nothing
+        let dfa = create_dfa_from_reg_ex("concat(star(symbol(a)),empty())").unwrap();
+        assert!(dfa.run("").is_ok_and(|res| !res));
+        assert!(dfa.run("aa").is_ok_and(|res| !res));
+
+        // while the star of the empty language is the empty string
+        let dfa = create_dfa_from_reg_ex("star(empty())").unwrap();
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+
+        let err = parse_reg_ex("eps(symbol(a))").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.expected, "`)` after `eps(`");
+    }
+
+    #[test]
+    fn check_escapes_and_unicode() {
+        let cases = [
+            ("symbol())", ')'),
+            ("symbol(,)", ','),
+            ("symbol(\\))", ')'),
+            ("symbol(\\\\)", '\\'),
+            ("symbol(\\n)", '\n'),
+            ("symbol(\\t)", '\t'),
+            ("symbol(\\u{e9})", 'é'),
+            ("symbol(\\u{1F600})", '😀'),
+            ("symbol(é)", 'é'),
+            ("symbol(日)", '日'),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_reg_ex(input).unwrap(),
+                RegexAst::Symbol(expected),
+                "{}",
+                input
+            );
+        }
+
+        let dfa = create_dfa_from_reg_ex("concat(symbol(日),star(symbol(\\t)))").unwrap();
+        assert!(dfa.run("日\t\t").is_ok_and(|res| res));
+        assert!(dfa.run("日 ").is_ok_and(|res| !res));
+
+        let dfa = create_dfa_from_reg_ex("class(\\u{3b1}-\\u{3c9})").unwrap();
+        assert!(dfa.run("λ").is_ok_and(|res| res));
+        assert!(dfa.run("l").is_ok_and(|res| !res));
+
+        let err = parse_reg_ex("symbol(\\u{d800})").unwrap_err();
+        assert_eq!(err.position, 10);
+        assert_eq!(err.expected, "a code point which is not a surrogate");
+
+        let err = parse_reg_ex("symbol(\\u{1234567})").unwrap_err();
+        assert_eq!(err.position, 16);
+
+        let err = parse_reg_ex("symbol(\\u{})").unwrap_err();
+        assert_eq!(err.expected, "a hexadecimal code point after `\\u{`");
+    }
+
+    #[test]
+    fn check_boolean_operators() {
+        // identifiers which are not keywords
+        let dfa = create_dfa_from_reg_ex(
+            "diff(plus(class(a-z)),union(concat(symbol(i),symbol(f)),concat(symbol(d),symbol(o))))",
+        )
+        .unwrap();
+        for (input, expected) in [("x", true), ("iff", true), ("if", false), ("do", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        // strings containing both an a and a b
+        let dfa = create_dfa_from_reg_ex(
+            "inter(concat(star(any()),concat(symbol(a),star(any()))),\
+             concat(star(any()),concat(symbol(b),star(any()))))",
+        )
+        .unwrap();
+        for (input, expected) in [("ab", true), ("xbxxa", true), ("aaa", false), ("", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        let dfa = create_dfa_from_reg_ex("not(star(symbol(a)))").unwrap();
+        for (input, expected) in [("", false), ("aaa", false), ("ab", true), ("é", true)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn check_parsing_round_trip() {
+        let inputs = [
+            "star(symbol(a))",
+            "concat(star(symbol(a)),union(symbol(b),symbol(c)))",
+            "concat(concat(symbol(0),symbol(1)),star(union(symbol(0),symbol(1))))",
+            "union(symbol(a),union(symbol(b),star(star(symbol(c)))))",
+            "concat(class(a-z0-9_),star(class(^\\-\\)\\\\)))",
+            "concat(star(any()),symbol(.))",
+            "union(eps(),concat(symbol(a),empty()))",
+            "inter(not(symbol(a)),diff(star(any()),concat(symbol(-),symbol(&))))",
+            "concat(symbol(\\\\),concat(symbol(\\n),symbol(\\u{7})))",
+            "concat(plus(symbol(a)),opt(repeat(symbol(b),2,)))",
+        ];
+
+        for input in inputs {
+            let ast = parse_reg_ex(input).unwrap();
+            assert_eq!(ast.to_string(), input);
+
+            // the infix form parses back into the same tree
+            let infix = ast.infix().to_string();
+            assert_eq!(
+                crate::infix_parsing::parse_infix_reg_ex(&infix).unwrap(),
+                ast
+            );
+        }
+    }
+
+    #[test]
+    fn check_error_positions() {
+        let err = parse_reg_ex("concat(symbol(a)symbol(b))").unwrap_err();
+        assert_eq!(err.position, 16);
+        assert_eq!(err.found, Some('s'));
+        assert_eq!(err.expected, "`,` after first operand of concat");
+        assert_eq!(
+            err.to_string(),
+            "unexpected `s` at offset 16, expected `,` after first operand of concat"
+        );
+        assert_eq!(
+            err.snippet(),
+            "concat(symbol(a)symbol(b))\n                ^"
+        );
+
+        let err = parse_reg_ex("union(symbol(a),star(symbol(b))").unwrap_err();
+        assert_eq!(err.position, 31);
+        assert_eq!(err.found, None);
+        assert_eq!(err.expected, "`)` after second operand of union");
+
+        let err = parse_reg_ex("star(cross(symbol(a)))").unwrap_err();
+        assert_eq!(err.position, 5);
+        assert_eq!(err.found, Some('c'));
+
+        let err = parse_reg_ex("symbol(ab)").unwrap_err();
+        assert_eq!(err.position, 8);
+        assert_eq!(err.expected, "`)` after the single character of symbol");
+
+        let err = parse_reg_ex("star(symbol(a)))").unwrap_err();
+        assert_eq!(err.position, 15);
+        assert_eq!(err.expected, "end of input after the reg-ex");
+    }
+
+    #[test]
+    fn check_long_snippet() {
+        let input = format!("{}symbol(a)x{}", "concat(".repeat(40), ")".repeat(40));
+        let err = parse_reg_ex(&input).unwrap_err();
+
+        let snippet = err.snippet();
+        let lines: Vec<&str> = snippet.lines().collect();
+        assert!(lines[0].starts_with("..."));
+        assert!(lines[0].ends_with("..."));
+
+        // the caret is under the offending character
+        let caret = lines[1].len() - 1;
+        assert_eq!(lines[0].chars().nth(caret), Some('x'));
+    }
+
+    #[test]
+    fn check_size_limit() {
+        // the copies of the repetition are never built
+        let limit_exceeded = RegexError::SizeLimitExceeded {
+            limit: DEFAULT_SIZE_LIMIT,
+        };
+        assert_eq!(
+            create_nfa_from_reg_ex("repeat(symbol(a),4000000000,)").unwrap_err(),
+            limit_exceeded
+        );
+        assert_eq!(
+            create_dfa_from_reg_ex("repeat(symbol(a),4000000000,)").unwrap_err(),
+            limit_exceeded
+        );
+    }
+
+    #[test]
+    fn check_nesting_limit() {
+        let nested = |depth: usize| {
+            format!(
+                "{}symbol(a){}",
+                "concat(symbol(a),".repeat(depth),
+                ")".repeat(depth)
+            )
+        };
+
+        // the innermost symbol is one more level
+        assert!(create_nfa_from_reg_ex(&nested(MAX_NESTING_DEPTH - 1)).is_ok());
+
+        let input = nested(3000);
+        let err = parse_reg_ex(&input).unwrap_err();
+        // the error is at the first operator nested too deep
+        assert_eq!(
+            err.position,
+            "concat(symbol(a),".len() * (MAX_NESTING_DEPTH - 1) + "concat(".len()
+        );
+        assert_eq!(err.found, Some('s'));
+        assert!(err.expected.contains("nested"));
+        assert!(err
+            .snippet()
+            .lines()
+            .nth(1)
+            .is_some_and(|line| line.ends_with('^')));
+    }
+
+    #[test]
+    fn check_invalid_reg_ex() {
+        let inputs = [
+            "",
+            "star(symbol(a)",
+            "star(symbol(a)))",
+            "concat(symbol(a))",
+            "union(symbol(a),)",
+            "symbol()",
+            "symbol(ab)",
+            "cross(symbol(a))",
+            "repeat(symbol(a),3,2)",
+            "repeat(symbol(a),,2)",
+            "repeat(symbol(a),99999999999,)",
+        ];
+
+        for input in inputs {
+            assert!(parse_reg_ex(input).is_err(), "{}", input);
+        }
+    }
 }
@@ -0,0 +1,138 @@
+//! This module contains the command line handling shared by the search mode and the
+//! subcommands, the flags choosing how a pattern is written and matched
+//!
+
+use grep_like_tool::{RegexBuilder, RegexError, Syntax};
+
+/// Options for compiling the patterns given on the command line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternOptions {
+    /// -E or --infix, the patterns are written in the infix syntax instead of `concat(...)`
+    pub infix: bool,
+    /// -i, match regardless of case
+    pub ignore_case: bool,
+}
+
+/// splits the command line arguments into the positional ones and the options,
+/// -E, -i and --infix are stored in `options` and every other short flag is passed
+/// to `other_flag`, which returns false if it does not know the flag
+pub fn parse_flags<F: FnMut(char) -> bool>(
+    args: &[String],
+    options: &mut PatternOptions,
+    mut other_flag: F,
+) -> Result<Vec<String>, String> {
+    let mut positional: Vec<String> = Vec::new();
+    let mut only_positional = false;
+
+    for arg in args {
+        if only_positional || arg == "-" || !arg.starts_with('-') {
+            positional.push(arg.clone());
+            continue;
+        }
+
+        if arg == "--" {
+            only_positional = true;
+            continue;
+        }
+
+        if arg == "--infix" {
+            options.infix = true;
+            continue;
+        }
+
+        if arg.starts_with("--") {
+            return Err(format!("unknown option {}", arg));
+        }
+
+        // short flags can be combined, as in -cn
+        for flag in arg.chars().skip(1) {
+            match flag {
+                'E' => options.infix = true,
+                'i' => options.ignore_case = true,
+                _ if other_flag(flag) => {}
+                _ => return Err(format!("unknown option -{}", flag)),
+            }
+        }
+    }
+
+    Ok(positional)
+}
+
+/// a builder for `pattern` with the syntax and case handling of the options
+pub fn regex_builder(pattern: &str, options: &PatternOptions) -> RegexBuilder {
+    let mut builder = RegexBuilder::new(pattern);
+    builder
+        .syntax(if options.infix {
+            Syntax::Infix
+        } else {
+            Syntax::Prefix
+        })
+        .case_insensitive(options.ignore_case);
+
+    builder
+}
+
+/// the message reported for a pattern which does not compile,
+/// with the offending part of it for parse errors
+pub fn error_message(err: &RegexError) -> String {
+    match err {
+        RegexError::Parse(err) => format!("invalid pattern: {}\n{}", err, err.snippet()),
+        err => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn check_parsing_flags() {
+        let mut options = PatternOptions::default();
+        let mut others = Vec::new();
+        let positional = parse_flags(
+            &args(&["-Ex", "a", "-", "--", "-i"]),
+            &mut options,
+            |flag| {
+                others.push(flag);
+                flag == 'x'
+            },
+        )
+        .unwrap();
+        assert_eq!(positional, args(&["a", "-", "-i"]));
+        assert_eq!(others, vec!['x']);
+        assert_eq!(
+            options,
+            PatternOptions {
+                infix: true,
+                ignore_case: false,
+            }
+        );
+
+        let mut options = PatternOptions::default();
+        parse_flags(&args(&["--infix", "-i"]), &mut options, |_| false).unwrap();
+        assert!(options.infix);
+        assert!(options.ignore_case);
+
+        let mut options = PatternOptions::default();
+        assert!(parse_flags(&args(&["-q"]), &mut options, |_| false).is_err());
+        assert!(parse_flags(&args(&["--bogus"]), &mut options, |_| true).is_err());
+    }
+
+    #[test]
+    fn check_building_regex() {
+        let options = PatternOptions {
+            infix: true,
+            ignore_case: true,
+        };
+        let regex = regex_builder("ab*", &options).build().unwrap();
+        assert!(regex.is_full_match("ABb"));
+        assert!(!regex.is_full_match("ba"));
+
+        let err = regex_builder("ab|", &options).build().unwrap_err();
+        assert!(error_message(&err).starts_with("invalid pattern: "));
+    }
+}
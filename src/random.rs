@@ -0,0 +1,104 @@
+//! This module contains the source of randomness for sampling the strings of a language,
+//! kept small so the crate needs no dependency for it
+//!
+
+use crate::big_count::BigCount;
+
+/// A source of uniformly distributed random numbers
+pub trait RandomSource {
+    /// returns the next uniformly distributed u64
+    fn next_u64(&mut self) -> u64;
+
+    /// returns a uniformly distributed number below `bound`, which must not be 0
+    fn below(&mut self, bound: u64) -> u64 {
+        // numbers from the incomplete last block of `bound` values would be more likely
+        let limit = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return value % bound;
+            }
+        }
+    }
+
+    /// returns a uniformly distributed number below `bound`, which must not be 0
+    fn below_big(&mut self, bound: &BigCount) -> BigCount {
+        let digits = bound.digits();
+        let top = digits[digits.len() - 1];
+        // the bits of the top digit which can be set in a number below `bound`
+        let mask = u32::MAX >> top.leading_zeros();
+
+        loop {
+            let mut candidate: Vec<u32> =
+                (0..digits.len()).map(|_| self.next_u64() as u32).collect();
+            candidate[digits.len() - 1] &= mask;
+
+            let candidate = BigCount::from_digits(candidate);
+            if candidate < *bound {
+                return candidate;
+            }
+        }
+    }
+}
+
+/// The SplitMix64 generator, fast and good enough for generating test inputs,
+/// but not for anything needing unpredictable numbers
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    /// the same seed always gives the same sequence of numbers
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl RandomSource for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_numbers_below_bound() {
+        let mut rng = SplitMix64::new(7);
+
+        let mut seen = [false; 6];
+        for _ in 0..200 {
+            seen[rng.below(6) as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+
+        // 2^64 + 1 only allows numbers up to 2^64
+        let bound = BigCount::from(u64::MAX) + BigCount::from(2);
+        for _ in 0..100 {
+            assert!(rng.below_big(&bound) < bound);
+        }
+        assert_eq!(rng.below_big(&BigCount::one()), BigCount::zero());
+    }
+
+    #[test]
+    fn check_same_seed_same_numbers() {
+        let first: Vec<u64> = {
+            let mut rng = SplitMix64::new(42);
+            (0..5).map(|_| rng.next_u64()).collect()
+        };
+        let mut rng = SplitMix64::new(42);
+        let second: Vec<u64> = (0..5).map(|_| rng.next_u64()).collect();
+
+        assert_eq!(first, second);
+        assert_ne!(first[0], first[1]);
+    }
+}
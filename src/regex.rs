@@ -0,0 +1,416 @@
+//! This module contains the `Regex` type, which compiles a reg-ex once and matches it many times
+//!
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    sync::OnceLock,
+};
+
+use thiserror::Error;
+
+use crate::{
+    dfa::DFA,
+    infix_parsing,
+    parsing::{self, ParsingError},
+    search::{FindIter, Match},
+    state::State,
+};
+
+/// default limit on the number of states of the NFA and of the DFA of a reg-ex
+pub const DEFAULT_SIZE_LIMIT: usize = 10_000;
+
+/// Error in compiling a reg-ex
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum RegexError {
+    #[error("{0}")]
+    Parse(#[from] ParsingError),
+
+    #[error("the reg-ex needs more than {limit} states")]
+    SizeLimitExceeded { limit: usize },
+}
+
+/// The syntax a reg-ex is written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// `concat(star(symbol(a)),symbol(b))`
+    #[default]
+    Prefix,
+    /// `a*b`
+    Infix,
+}
+
+/// Options for compiling a reg-ex
+#[derive(Clone, Debug)]
+pub struct RegexBuilder {
+    pattern: String,
+    syntax: Syntax,
+    anchored: bool,
+    case_insensitive: bool,
+    size_limit: usize,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> RegexBuilder {
+        RegexBuilder {
+            pattern: pattern.to_string(),
+            syntax: Syntax::default(),
+            anchored: false,
+            case_insensitive: false,
+            size_limit: DEFAULT_SIZE_LIMIT,
+        }
+    }
+
+    /// the syntax the pattern is written in, prefix by default
+    pub fn syntax(&mut self, syntax: Syntax) -> &mut RegexBuilder {
+        self.syntax = syntax;
+        self
+    }
+
+    /// if set, a match has to cover the whole string instead of any substring of it
+    pub fn anchored(&mut self, anchored: bool) -> &mut RegexBuilder {
+        self.anchored = anchored;
+        self
+    }
+
+    /// if set, characters match regardless of their case
+    pub fn case_insensitive(&mut self, case_insensitive: bool) -> &mut RegexBuilder {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// limit on the number of states of the NFA and of the DFA built for the pattern
+    pub fn size_limit(&mut self, size_limit: usize) -> &mut RegexBuilder {
+        self.size_limit = size_limit;
+        self
+    }
+
+    /// compiles the pattern into its minimized DFA
+    pub fn build(&self) -> Result<Regex, RegexError> {
+        let ast = match self.syntax {
+            Syntax::Prefix => parsing::parse_reg_ex(&self.pattern)?,
+            Syntax::Infix => infix_parsing::parse_infix_reg_ex(&self.pattern)?,
+        };
+        let ast = if self.case_insensitive {
+            ast.case_insensitive()
+        } else {
+            ast
+        };
+
+        let size_limit_exceeded = || RegexError::SizeLimitExceeded {
+            limit: self.size_limit,
+        };
+
+        // every step gives up as soon as it goes over the limit
+        let dfa = ast
+            .to_dfa_with_size_limit(self.size_limit)
+            .ok_or_else(size_limit_exceeded)?;
+
+        Ok(Regex {
+            pattern: self.pattern.clone(),
+            anchored: self.anchored,
+            size_limit: self.size_limit,
+            reverse: OnceLock::new(),
+            live_states: dfa.live_states(),
+            substring_dfa: dfa.try_substring_dfa(self.size_limit),
+            dfa,
+        })
+    }
+}
+
+/// A compiled reg-ex, holding the minimized DFA of its language
+#[derive(Clone, Debug)]
+pub struct Regex {
+    pattern: String,
+    anchored: bool,
+    dfa: DFA,
+    size_limit: usize,
+    // DFA of the reversed language, which marks where matches start, only the searches
+    // for the positions of matches need it, so the first of them builds it,
+    // `None` if it is over the size limit
+    reverse: OnceLock<Option<DFA>>,
+    // states of `dfa` from which a final state can be reached
+    live_states: HashSet<State>,
+    // DFA of the strings containing a match, `None` if it is over the size limit,
+    // in which case the search runs the sets of states of `dfa` instead
+    substring_dfa: Option<DFA>,
+}
+
+impl Regex {
+    /// compiles a reg-ex written in the prefix syntax, e.g. `concat(symbol(a),star(symbol(b)))`
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    /// returns the pattern the reg-ex was compiled from
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// returns the minimized DFA of the reg-ex
+    pub fn dfa(&self) -> &DFA {
+        &self.dfa
+    }
+
+    /// to check if `s` contains a match, for an anchored reg-ex the whole of `s` has to match
+    pub fn is_match(&self, s: &str) -> bool {
+        match &self.substring_dfa {
+            _ if self.anchored => self.is_full_match(s),
+            // complete like `dfa`, see `is_full_match`
+            Some(substring_dfa) => substring_dfa
+                .run(s)
+                .unwrap_or_else(|err| panic!("the DFA of a reg-ex is complete, {}", err)),
+            None => self.dfa.is_match_anywhere(s),
+        }
+    }
+
+    /// to check if the whole of `s` matches
+    pub fn is_full_match(&self, s: &str) -> bool {
+        // run only fails on a state without transitions, but the subset construction
+        // gives every state, the empty subset included, a transition on every symbol
+        // and lookup maps every character to a symbol, so an error is a bug in the DFA
+        self.dfa
+            .run(s)
+            .unwrap_or_else(|err| panic!("the DFA of a reg-ex is complete, {}", err))
+    }
+
+    /// returns the leftmost-longest match in `s`
+    pub fn find(&self, s: &str) -> Option<Match> {
+        self.find_iter(s).next()
+    }
+
+    /// returns an iterator over the non-overlapping leftmost-longest matches in `s`
+    pub fn find_iter<'r, 's>(&'r self, s: &'s str) -> FindIter<'r, 's> {
+        let match_starts = if self.anchored {
+            let mut match_starts = vec![false; s.len() + 1];
+            match_starts[0] = true;
+            match_starts
+        } else {
+            match self
+                .reverse
+                .get_or_init(|| self.dfa.try_reverse(self.size_limit))
+            {
+                Some(reverse) => reverse.accepting_suffix_starts(s),
+                // without the reverse pass a match is looked for at every offset
+                None => vec![true; s.len() + 1],
+            }
+        };
+
+        FindIter::new(
+            &self.dfa,
+            s,
+            match_starts,
+            Cow::Borrowed(&self.live_states),
+            self.anchored,
+        )
+    }
+
+    /// returns an iterator over the parts of `s` between the matches
+    pub fn split<'r, 's>(&'r self, s: &'s str) -> Split<'r, 's> {
+        Split {
+            matches: self.find_iter(s),
+            haystack: s,
+            last: Some(0),
+        }
+    }
+
+    /// replaces the first match in `s` with `replacement`
+    pub fn replace(&self, s: &str, replacement: &str) -> String {
+        self.replacen(s, 1, replacement)
+    }
+
+    /// replaces every match in `s` with `replacement`
+    pub fn replace_all(&self, s: &str, replacement: &str) -> String {
+        self.replacen(s, 0, replacement)
+    }
+
+    /// replaces the first `limit` matches in `s` with `replacement`, all of them if `limit` is 0
+    pub fn replacen(&self, s: &str, limit: usize, replacement: &str) -> String {
+        let mut result = String::with_capacity(s.len());
+        let mut last = 0;
+
+        for (count, found) in self.find_iter(s).enumerate() {
+            if limit > 0 && count == limit {
+                break;
+            }
+
+            result.push_str(&s[last..found.start]);
+            result.push_str(replacement);
+            last = found.end;
+        }
+
+        result.push_str(&s[last..]);
+        result
+    }
+}
+
+impl Display for Regex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+/// Iterator over the parts of a string between the matches of a reg-ex
+pub struct Split<'r, 's> {
+    matches: FindIter<'r, 's>,
+    haystack: &'s str,
+    // start of the next part, `None` once the last part was returned
+    last: Option<usize>,
+}
+
+impl<'s> Iterator for Split<'_, 's> {
+    type Item = &'s str;
+
+    fn next(&mut self) -> Option<&'s str> {
+        let last = self.last?;
+
+        match self.matches.next() {
+            Some(found) => {
+                self.last = Some(found.end);
+                Some(&self.haystack[last..found.start])
+            }
+            None => {
+                self.last = None;
+                Some(&self.haystack[last..])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_matching() {
+        let regex = Regex::new("concat(symbol(a),star(symbol(b)))").unwrap();
+        assert_eq!(regex.as_str(), "concat(symbol(a),star(symbol(b)))");
+
+        assert!(regex.is_match("xxabbx"));
+        assert!(!regex.is_match("xxbbx"));
+
+        assert!(regex.is_full_match("abb"));
+        assert!(!regex.is_full_match("xabb"));
+
+        assert_eq!(regex.find("xabbx"), Some(Match { start: 1, end: 4 }));
+
+        let found: Vec<&str> = regex
+            .find_iter("ab a abbb")
+            .map(|found| found.as_str("ab a abbb"))
+            .collect();
+        assert_eq!(found, vec!["ab", "a", "abbb"]);
+    }
+
+    #[test]
+    fn check_builder_options() {
+        let regex = RegexBuilder::new("(a|b)*c")
+            .syntax(Syntax::Infix)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("xxabcx"));
+
+        let regex = RegexBuilder::new("ab*")
+            .syntax(Syntax::Infix)
+            .anchored(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("abb"));
+        assert!(!regex.is_match("xabb"));
+        assert!(!regex.is_match("abbx"));
+        assert_eq!(regex.find("abb"), Some(Match { start: 0, end: 3 }));
+        assert_eq!(regex.find("abbx"), None);
+        assert_eq!(regex.find_iter("abb").count(), 1);
+
+        let regex = RegexBuilder::new("ab")
+            .syntax(Syntax::Infix)
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("xAbx"));
+        assert!(regex.is_match("AB"));
+        assert!(!regex.is_match("A B"));
+    }
+
+    #[test]
+    fn check_size_limit() {
+        let pattern = "(a|b)*a(a|b)(a|b)(a|b)(a|b)";
+
+        // the DFA has to remember the last five characters
+        let err = RegexBuilder::new(pattern)
+            .syntax(Syntax::Infix)
+            .size_limit(20)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, RegexError::SizeLimitExceeded { limit: 20 });
+
+        let regex = RegexBuilder::new(pattern)
+            .syntax(Syntax::Infix)
+            .build()
+            .unwrap();
+        assert!(regex.is_full_match("abbbb"));
+
+        // searching without the DFA of the strings containing a match, which is over the limit
+        let regex = RegexBuilder::new("a(a|b){4}")
+            .syntax(Syntax::Infix)
+            .size_limit(30)
+            .build()
+            .unwrap();
+        assert!(regex.substring_dfa.is_none());
+        assert!(regex.is_match("bbabbbb"));
+        assert!(!regex.is_match("bbbbabb"));
+
+        // the copies of a large repetition are never built
+        let err = RegexBuilder::new("repeat(symbol(a),0,2000000)")
+            .size_limit(100)
+            .build()
+            .unwrap_err();
+        assert_eq!(err, RegexError::SizeLimitExceeded { limit: 100 });
+
+        // the reversed DFA has to remember the last eleven characters, the DFA does not
+        let regex = RegexBuilder::new("[ab]{10}a[ab]*")
+            .syntax(Syntax::Infix)
+            .size_limit(1000)
+            .build()
+            .unwrap();
+        assert!(regex.is_match("bbbbbbbbbbbba"));
+        assert_eq!(
+            regex.find("bbbbbbbbbbbba"),
+            Some(Match { start: 2, end: 13 })
+        );
+        assert!(regex.reverse.get().is_some_and(|reverse| reverse.is_none()));
+
+        let matches: Vec<Match> = regex.find_iter("bbbbbbbbbbba,bbbbbbbbbbba").collect();
+        assert_eq!(
+            matches,
+            vec![Match { start: 1, end: 12 }, Match { start: 14, end: 25 }]
+        );
+    }
+
+    #[test]
+    fn check_split_and_replace() {
+        let regex = RegexBuilder::new(",  *")
+            .syntax(Syntax::Infix)
+            .build()
+            .unwrap();
+
+        let parts: Vec<&str> = regex.split("a, b,c,   d").collect();
+        assert_eq!(parts, vec!["a", "b,c", "d"]);
+
+        let parts: Vec<&str> = regex.split(", a, ").collect();
+        assert_eq!(parts, vec!["", "a", ""]);
+
+        assert_eq!(regex.replace("a, b, c", ";"), "a;b, c");
+        assert_eq!(regex.replace_all("a, b, c", ";"), "a;b;c");
+        assert_eq!(regex.replacen("a, b, c, d", 2, ";"), "a;b;c, d");
+        assert_eq!(regex.replace_all("abc", ";"), "abc");
+    }
+
+    #[test]
+    fn check_invalid_pattern() {
+        match Regex::new("concat(symbol(a))").unwrap_err() {
+            RegexError::Parse(err) => assert_eq!(err.position, 16),
+            err => panic!("unexpected error {}", err),
+        }
+    }
+}
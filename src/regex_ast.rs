@@ -0,0 +1,834 @@
+//! This module contains the syntax tree of a reg-ex, which both parsers produce
+//!
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::{
+    custom_errors::AutomatonError,
+    dfa::{BoolOp, DFA},
+    nfa::NFA,
+    symbol_table::{Symbol, SymbolTable},
+};
+
+type Stack<T> = Vec<T>;
+
+/// characters which have to be escaped when printed in the infix syntax
+pub const INFIX_OPERATORS: [char; 13] = [
+    '|', '+', '&', '-', '~', '*', '?', '{', '(', ')', '[', '.', '\\',
+];
+
+/// characters which have to be escaped inside a character class, in both syntaxes
+pub const CLASS_OPERATORS: [char; 5] = ['-', '^', ']', ')', '\\'];
+
+/// Syntax tree of a reg-ex
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RegexAst {
+    /// the empty language
+    Empty,
+    /// the language containing only the empty string
+    Epsilon,
+    Symbol(char),
+    /// any single character
+    Any,
+    /// a character class, matching any character of the ranges or,
+    /// when negated, any character outside of them
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Concat(Box<RegexAst>, Box<RegexAst>),
+    Union(Box<RegexAst>, Box<RegexAst>),
+    /// strings in both languages
+    Intersection(Box<RegexAst>, Box<RegexAst>),
+    /// strings in the first language but not in the second
+    Difference(Box<RegexAst>, Box<RegexAst>),
+    /// every string not in the language
+    Complement(Box<RegexAst>),
+    Star(Box<RegexAst>),
+    /// at least `min` and at most `max` repetitions, `max` of `None` is unbounded
+    Repeat {
+        inner: Box<RegexAst>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+/// constructors
+impl RegexAst {
+    pub fn concat(first: RegexAst, second: RegexAst) -> RegexAst {
+        RegexAst::Concat(Box::new(first), Box::new(second))
+    }
+
+    pub fn union(first: RegexAst, second: RegexAst) -> RegexAst {
+        RegexAst::Union(Box::new(first), Box::new(second))
+    }
+
+    pub fn star(inner: RegexAst) -> RegexAst {
+        RegexAst::Star(Box::new(inner))
+    }
+
+    pub fn intersection(first: RegexAst, second: RegexAst) -> RegexAst {
+        RegexAst::Intersection(Box::new(first), Box::new(second))
+    }
+
+    pub fn difference(first: RegexAst, second: RegexAst) -> RegexAst {
+        RegexAst::Difference(Box::new(first), Box::new(second))
+    }
+
+    pub fn complement(inner: RegexAst) -> RegexAst {
+        RegexAst::Complement(Box::new(inner))
+    }
+
+    /// one or more repetitions
+    pub fn plus(inner: RegexAst) -> RegexAst {
+        RegexAst::repeat(inner, 1, None)
+    }
+
+    /// zero or one repetition
+    pub fn optional(inner: RegexAst) -> RegexAst {
+        RegexAst::repeat(inner, 0, Some(1))
+    }
+
+    pub fn repeat(inner: RegexAst, min: u32, max: Option<u32>) -> RegexAst {
+        RegexAst::Repeat {
+            inner: Box::new(inner),
+            min,
+            max,
+        }
+    }
+
+    /// a class without ranges matches nothing, so it is the empty language
+    pub fn class(ranges: Vec<(char, char)>, negated: bool) -> RegexAst {
+        if ranges.is_empty() && !negated {
+            return RegexAst::Empty;
+        }
+
+        RegexAst::Class { ranges, negated }
+    }
+}
+
+impl RegexAst {
+    /// returns the characters used in the symbols of the reg-ex, character classes excluded
+    pub fn symbols(&self) -> HashSet<char> {
+        let mut symbols = HashSet::new();
+        self.collect_symbols(&mut symbols);
+
+        symbols
+    }
+
+    fn collect_symbols(&self, symbols: &mut HashSet<char>) {
+        match self {
+            RegexAst::Empty | RegexAst::Epsilon | RegexAst::Any | RegexAst::Class { .. } => {}
+            RegexAst::Symbol(ch) => {
+                symbols.insert(*ch);
+            }
+            RegexAst::Concat(first, second)
+            | RegexAst::Union(first, second)
+            | RegexAst::Intersection(first, second)
+            | RegexAst::Difference(first, second) => {
+                first.collect_symbols(symbols);
+                second.collect_symbols(symbols);
+            }
+            RegexAst::Star(inner)
+            | RegexAst::Repeat { inner, .. }
+            | RegexAst::Complement(inner) => inner.collect_symbols(symbols),
+        }
+    }
+
+    /// returns the symbol table containing every character and range of the reg-ex
+    pub fn symbol_table(&self) -> SymbolTable {
+        let mut symbol_table = SymbolTable::new();
+        self.add_to_symbol_table(&mut symbol_table);
+
+        symbol_table
+    }
+
+    fn add_to_symbol_table(&self, symbol_table: &mut SymbolTable) {
+        match self {
+            RegexAst::Empty | RegexAst::Epsilon | RegexAst::Any => {}
+            RegexAst::Symbol(ch) => symbol_table.add_character(*ch),
+            RegexAst::Class { ranges, .. } => {
+                for &(lo, hi) in ranges {
+                    symbol_table.add_range(lo, hi);
+                }
+            }
+            RegexAst::Concat(first, second)
+            | RegexAst::Union(first, second)
+            | RegexAst::Intersection(first, second)
+            | RegexAst::Difference(first, second) => {
+                first.add_to_symbol_table(symbol_table);
+                second.add_to_symbol_table(symbol_table);
+            }
+            RegexAst::Star(inner)
+            | RegexAst::Repeat { inner, .. }
+            | RegexAst::Complement(inner) => inner.add_to_symbol_table(symbol_table),
+        }
+    }
+
+    /// creates an NFA accepting the language of the reg-ex
+    pub fn to_nfa(&self) -> NFA {
+        self.to_nfa_with_symbol_table(&self.symbol_table())
+    }
+
+    /// creates a minimized DFA accepting the language of the reg-ex
+    pub fn to_dfa(&self) -> DFA {
+        DFA::convert_to_dfa(self.to_nfa())
+    }
+
+    /// creates an NFA accepting the language of the reg-ex, `None` if it, or the DFA of
+    /// an operand of a boolean operator, needs more than `size_limit` states
+    pub fn to_nfa_with_size_limit(&self, size_limit: usize) -> Option<NFA> {
+        // a large repetition is rejected before any of its copies is built
+        if self.nfa_size() > size_limit {
+            return None;
+        }
+
+        match self.try_to_nfa_with_symbol_table(&self.symbol_table(), Some(size_limit)) {
+            Ok(nfa) => Some(nfa),
+            Err(AutomatonError::SizeLimitExceeded(_)) => None,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// creates a minimized DFA accepting the language of the reg-ex, `None` if it, or an
+    /// automaton built on the way, needs more than `size_limit` states
+    pub fn to_dfa_with_size_limit(&self, size_limit: usize) -> Option<DFA> {
+        DFA::try_convert_to_dfa(self.to_nfa_with_size_limit(size_limit)?, size_limit)
+    }
+
+    /// returns the number of states of the NFA built for the reg-ex, without the states
+    /// of the DFAs which the boolean operators build from their operands
+    fn nfa_size(&self) -> usize {
+        match self {
+            RegexAst::Epsilon => 1,
+            RegexAst::Empty | RegexAst::Symbol(_) | RegexAst::Any | RegexAst::Class { .. } => 2,
+            RegexAst::Concat(first, second) => {
+                first.nfa_size().saturating_add(second.nfa_size()) + 1
+            }
+            RegexAst::Union(first, second) => {
+                first.nfa_size().saturating_add(second.nfa_size()) + 2
+            }
+            RegexAst::Intersection(first, second) | RegexAst::Difference(first, second) => {
+                first.nfa_size().saturating_add(second.nfa_size())
+            }
+            RegexAst::Complement(inner) => inner.nfa_size(),
+            RegexAst::Star(inner) => inner.nfa_size().saturating_add(2),
+            RegexAst::Repeat { inner, min, max } => {
+                let num_copies = max.map_or(*min as usize + 1, |max| max.max(*min) as usize);
+
+                inner
+                    .nfa_size()
+                    .saturating_mul(num_copies)
+                    .saturating_add(2)
+            }
+        }
+    }
+
+    /// the symbol table has to contain every character and range of the reg-ex
+    fn to_nfa_with_symbol_table(&self, symbol_table: &SymbolTable) -> NFA {
+        // every automaton is built over the same symbol table and there is no size limit,
+        // so combining them cannot fail
+        self.try_to_nfa_with_symbol_table(symbol_table, None)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// walks the tree with explicit stacks instead of recursion, as the baseline parser
+    /// did, so that a deeply nested reg-ex cannot overflow the call stack
+    fn try_to_nfa_with_symbol_table(
+        &self,
+        symbol_table: &SymbolTable,
+        size_limit: Option<usize>,
+    ) -> Result<NFA, AutomatonError> {
+        // (node, whether the NFAs of its operands are already on the NFA stack)
+        let mut node_stack: Stack<(&RegexAst, bool)> = vec![(self, false)];
+        let mut nfa_stack: Stack<NFA> = Stack::new();
+
+        while let Some((node, operands_built)) = node_stack.pop() {
+            if !operands_built {
+                node_stack.push((node, true));
+
+                // the first operand is pushed last, so that its NFA is built first
+                match node {
+                    RegexAst::Concat(first, second)
+                    | RegexAst::Union(first, second)
+                    | RegexAst::Intersection(first, second)
+                    | RegexAst::Difference(first, second) => {
+                        node_stack.push((second, false));
+                        node_stack.push((first, false));
+                    }
+                    RegexAst::Star(inner)
+                    | RegexAst::Repeat { inner, .. }
+                    | RegexAst::Complement(inner) => node_stack.push((inner, false)),
+                    _ => {}
+                }
+                continue;
+            }
+
+            let nfa = match node {
+                RegexAst::Empty => NFA::empty(symbol_table),
+                RegexAst::Epsilon => NFA::from_symbol(&Symbol::Epsilon, symbol_table),
+                RegexAst::Symbol(ch) => NFA::from_symbol(&symbol_table.lookup(*ch), symbol_table),
+                // Symbol::Other covers the characters the reg-ex does not mention
+                RegexAst::Any => {
+                    let symbols: Vec<Symbol> = symbol_table.input_symbols().collect();
+
+                    NFA::from_symbols(&symbols, symbol_table)
+                }
+                RegexAst::Class { ranges, negated } => {
+                    let mut symbols: HashSet<Symbol> = HashSet::new();
+                    for &(lo, hi) in ranges {
+                        symbols.extend(symbol_table.symbols_in_range(lo, hi).unwrap_or_else(
+                            || panic!("range {:?} is missing from the symbol table", (lo, hi)),
+                        ));
+                    }
+
+                    let symbols: Vec<Symbol> = if *negated {
+                        symbol_table
+                            .input_symbols()
+                            .filter(|symbol| !symbols.contains(symbol))
+                            .collect()
+                    } else {
+                        symbols.into_iter().collect()
+                    };
+
+                    NFA::from_symbols(&symbols, symbol_table)
+                }
+                RegexAst::Concat(..) => {
+                    let (first, second) = pop_operands(&mut nfa_stack);
+                    first.concat(second)?
+                }
+                RegexAst::Union(..) => {
+                    let (first, second) = pop_operands(&mut nfa_stack);
+                    first.union(second)?
+                }
+                // the boolean operators have no NFA construction, they go through the
+                // product and complement of the DFAs of their operands
+                RegexAst::Intersection(..) => {
+                    let (first, second) = pop_operands(&mut nfa_stack);
+                    NFA::convert_dfa_to_nfa(
+                        to_dfa(first, size_limit)?.intersection(to_dfa(second, size_limit)?)?,
+                    )
+                }
+                RegexAst::Difference(..) => {
+                    let (first, second) = pop_operands(&mut nfa_stack);
+                    NFA::convert_dfa_to_nfa(
+                        to_dfa(first, size_limit)?
+                            .product(&to_dfa(second, size_limit)?, BoolOp::Difference)?,
+                    )
+                }
+                RegexAst::Complement(_) => {
+                    let inner = pop_operand(&mut nfa_stack);
+                    NFA::convert_dfa_to_nfa(to_dfa(inner, size_limit)?.complement())
+                }
+                RegexAst::Star(_) => pop_operand(&mut nfa_stack).kleene_star(),
+                RegexAst::Repeat { min, max, .. } => {
+                    pop_operand(&mut nfa_stack).repeat(*min as usize, max.map(|max| max as usize))
+                }
+            };
+
+            match size_limit {
+                Some(limit) if nfa.num_states() > limit => {
+                    return Err(AutomatonError::SizeLimitExceeded(limit))
+                }
+                _ => nfa_stack.push(nfa),
+            }
+        }
+
+        Ok(pop_operand(&mut nfa_stack))
+    }
+
+    /// returns the reg-ex with every cased character replaced by the union of its
+    /// lowercase and uppercase forms, characters whose case mapping is not a single
+    /// character are kept as they are
+    pub fn case_insensitive(&self) -> RegexAst {
+        match self {
+            RegexAst::Empty | RegexAst::Epsilon | RegexAst::Any => self.clone(),
+            RegexAst::Symbol(ch) => {
+                let mut variants = vec![*ch];
+                for variant in [
+                    single_char(ch.to_lowercase()),
+                    single_char(ch.to_uppercase()),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    if !variants.contains(&variant) {
+                        variants.push(variant);
+                    }
+                }
+
+                variants
+                    .into_iter()
+                    .map(RegexAst::Symbol)
+                    .reduce(RegexAst::union)
+                    .unwrap_or_else(|| panic!("a character is always its own variant"))
+            }
+            RegexAst::Class { ranges, negated } => {
+                let mut variants: BTreeSet<char> = BTreeSet::new();
+                for &(lo, hi) in ranges {
+                    for ch in lo..=hi {
+                        variants.extend(single_char(ch.to_lowercase()));
+                        variants.extend(single_char(ch.to_uppercase()));
+                    }
+                }
+
+                let mut ranges = ranges.clone();
+                for ch in variants {
+                    if ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi) {
+                        continue;
+                    }
+
+                    // consecutive variants, like those of `a-z`, form a single range
+                    match ranges.last_mut() {
+                        Some((_, hi)) if char::from_u32(*hi as u32 + 1) == Some(ch) => *hi = ch,
+                        _ => ranges.push((ch, ch)),
+                    }
+                }
+
+                RegexAst::class(ranges, *negated)
+            }
+            RegexAst::Concat(first, second) => {
+                RegexAst::concat(first.case_insensitive(), second.case_insensitive())
+            }
+            RegexAst::Union(first, second) => {
+                RegexAst::union(first.case_insensitive(), second.case_insensitive())
+            }
+            RegexAst::Intersection(first, second) => {
+                RegexAst::intersection(first.case_insensitive(), second.case_insensitive())
+            }
+            RegexAst::Difference(first, second) => {
+                RegexAst::difference(first.case_insensitive(), second.case_insensitive())
+            }
+            RegexAst::Complement(inner) => RegexAst::complement(inner.case_insensitive()),
+            RegexAst::Star(inner) => RegexAst::star(inner.case_insensitive()),
+            RegexAst::Repeat { inner, min, max } => {
+                RegexAst::repeat(inner.case_insensitive(), *min, *max)
+            }
+        }
+    }
+
+    /// returns a value whose `Display` prints the reg-ex in the infix syntax,
+    /// `Display` of the reg-ex itself prints the prefix syntax
+    pub fn infix(&self) -> Infix<'_> {
+        Infix(self)
+    }
+
+    /// binding strength in the infix syntax, higher binds tighter
+    fn precedence(&self) -> u8 {
+        match self {
+            RegexAst::Union(_, _) => 0,
+            RegexAst::Intersection(_, _) | RegexAst::Difference(_, _) => 1,
+            RegexAst::Concat(_, _) => 2,
+            RegexAst::Complement(_) => 3,
+            RegexAst::Star(_) | RegexAst::Repeat { .. } => 4,
+            RegexAst::Empty
+            | RegexAst::Epsilon
+            | RegexAst::Symbol(_)
+            | RegexAst::Any
+            | RegexAst::Class { .. } => 5,
+        }
+    }
+}
+
+/// the NFA of the only operand, built before the node using it
+fn pop_operand(nfa_stack: &mut Stack<NFA>) -> NFA {
+    nfa_stack
+        .pop()
+        .unwrap_or_else(|| panic!("the operand is built before its operator"))
+}
+
+/// the NFAs of both operands, the second one is on top of the stack
+fn pop_operands(nfa_stack: &mut Stack<NFA>) -> (NFA, NFA) {
+    let second = pop_operand(nfa_stack);
+    let first = pop_operand(nfa_stack);
+
+    (first, second)
+}
+
+/// the DFA of an operand of a boolean operator
+fn to_dfa(nfa: NFA, size_limit: Option<usize>) -> Result<DFA, AutomatonError> {
+    match size_limit {
+        Some(limit) => {
+            DFA::try_convert_to_dfa(nfa, limit).ok_or(AutomatonError::SizeLimitExceeded(limit))
+        }
+        None => Ok(DFA::convert_to_dfa(nfa)),
+    }
+}
+
+/// returns the only character of a case mapping, `None` if it maps to several
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
+    }
+}
+
+/// prints the ranges of a class the same way in both syntaxes, e.g. `^a-z0-9_`
+fn fmt_class_items(ranges: &[(char, char)], negated: bool, f: &mut Formatter<'_>) -> fmt::Result {
+    if negated {
+        write!(f, "^")?;
+    }
+
+    for &(lo, hi) in ranges {
+        fmt_char(lo, &CLASS_OPERATORS, f)?;
+        if lo != hi {
+            write!(f, "-")?;
+            fmt_char(hi, &CLASS_OPERATORS, f)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// prints a character the way the parsers read it back, control characters as
+/// escape sequences and the `special` ones after a `\`
+fn fmt_char(ch: char, special: &[char], f: &mut Formatter<'_>) -> fmt::Result {
+    match ch {
+        '\n' => write!(f, "\\n"),
+        '\t' => write!(f, "\\t"),
+        '\r' => write!(f, "\\r"),
+        '\0' => write!(f, "\\0"),
+        _ if ch.is_control() => write!(f, "\\u{{{:x}}}", ch as u32),
+        _ if special.contains(&ch) => write!(f, "\\{}", ch),
+        _ => write!(f, "{}", ch),
+    }
+}
+
+/// prints the prefix syntax, e.g. `concat(star(symbol(a)),symbol(b))`
+impl Display for RegexAst {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexAst::Empty => write!(f, "empty()"),
+            RegexAst::Epsilon => write!(f, "eps()"),
+            RegexAst::Symbol(ch) => {
+                write!(f, "symbol(")?;
+                fmt_char(*ch, &['\\'], f)?;
+                write!(f, ")")
+            }
+            RegexAst::Any => write!(f, "any()"),
+            RegexAst::Class { ranges, negated } => {
+                write!(f, "class(")?;
+                fmt_class_items(ranges, *negated, f)?;
+                write!(f, ")")
+            }
+            RegexAst::Concat(first, second) => write!(f, "concat({},{})", first, second),
+            RegexAst::Union(first, second) => write!(f, "union({},{})", first, second),
+            RegexAst::Intersection(first, second) => write!(f, "inter({},{})", first, second),
+            RegexAst::Difference(first, second) => write!(f, "diff({},{})", first, second),
+            RegexAst::Complement(inner) => write!(f, "not({})", inner),
+            RegexAst::Star(inner) => write!(f, "star({})", inner),
+            RegexAst::Repeat {
+                inner,
+                min: 1,
+                max: None,
+            } => write!(f, "plus({})", inner),
+            RegexAst::Repeat {
+                inner,
+                min: 0,
+                max: Some(1),
+            } => write!(f, "opt({})", inner),
+            RegexAst::Repeat { inner, min, max } => {
+                write!(f, "repeat({},{},", inner, min)?;
+                if let Some(max) = max {
+                    write!(f, "{}", max)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Wrapper printing a reg-ex in the infix syntax, e.g. `a*b`
+pub struct Infix<'a>(&'a RegexAst);
+
+impl Infix<'_> {
+    /// prints `ast`, in parentheses if it binds weaker than `precedence`
+    fn fmt_operand(ast: &RegexAst, precedence: u8, f: &mut Formatter<'_>) -> fmt::Result {
+        if ast.precedence() < precedence {
+            write!(f, "({})", Infix(ast))
+        } else {
+            write!(f, "{}", Infix(ast))
+        }
+    }
+}
+
+impl Display for Infix<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            RegexAst::Empty => write!(f, "[]"),
+            RegexAst::Epsilon => write!(f, "()"),
+            RegexAst::Symbol(ch) => fmt_char(*ch, &INFIX_OPERATORS, f),
+            RegexAst::Any => write!(f, "."),
+            RegexAst::Class { ranges, negated } => {
+                write!(f, "[")?;
+                fmt_class_items(ranges, *negated, f)?;
+                write!(f, "]")
+            }
+            // the binary operators are parsed left associative, so a right operand of
+            // the same precedence is put in parentheses to print the same tree back
+            RegexAst::Concat(first, second) => {
+                Infix::fmt_operand(first, 2, f)?;
+                Infix::fmt_operand(second, 3, f)
+            }
+            RegexAst::Union(first, second) => {
+                Infix::fmt_operand(first, 0, f)?;
+                write!(f, "|")?;
+                Infix::fmt_operand(second, 1, f)
+            }
+            RegexAst::Intersection(first, second) | RegexAst::Difference(first, second) => {
+                Infix::fmt_operand(first, 1, f)?;
+                if matches!(self.0, RegexAst::Intersection(_, _)) {
+                    write!(f, "&")?;
+                } else {
+                    write!(f, "-")?;
+                }
+                Infix::fmt_operand(second, 2, f)
+            }
+            RegexAst::Complement(inner) => {
+                write!(f, "~")?;
+                Infix::fmt_operand(inner, 3, f)
+            }
+            RegexAst::Star(inner) => {
+                Infix::fmt_operand(inner, 4, f)?;
+                write!(f, "*")
+            }
+            RegexAst::Repeat { inner, min, max } => {
+                Infix::fmt_operand(inner, 4, f)?;
+                match (min, max) {
+                    (1, None) => write!(f, "+"),
+                    (0, Some(1)) => write!(f, "?"),
+                    (min, None) => write!(f, "{{{},}}", min),
+                    (min, Some(max)) if min == max => write!(f, "{{{}}}", min),
+                    (min, Some(max)) => write!(f, "{{{},{}}}", min, max),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(ch: char) -> RegexAst {
+        RegexAst::Symbol(ch)
+    }
+
+    #[test]
+    fn check_prefix_printing() {
+        let ast = RegexAst::concat(
+            RegexAst::star(symbol('a')),
+            RegexAst::union(symbol('b'), symbol('c')),
+        );
+        assert_eq!(
+            ast.to_string(),
+            "concat(star(symbol(a)),union(symbol(b),symbol(c)))"
+        );
+
+        assert_eq!(RegexAst::Epsilon.to_string(), "eps()");
+        assert_eq!(RegexAst::Empty.to_string(), "empty()");
+    }
+
+    #[test]
+    fn check_infix_printing() {
+        let ast = RegexAst::concat(
+            RegexAst::star(RegexAst::union(symbol('a'), symbol('b'))),
+            symbol('c'),
+        );
+        assert_eq!(ast.infix().to_string(), "(a|b)*c");
+
+        let ast = RegexAst::union(
+            RegexAst::concat(symbol('a'), symbol('b')),
+            RegexAst::star(symbol('c')),
+        );
+        assert_eq!(ast.infix().to_string(), "ab|c*");
+
+        // right nested operands keep their parentheses
+        let ast = RegexAst::concat(symbol('a'), RegexAst::concat(symbol('b'), symbol('c')));
+        assert_eq!(ast.infix().to_string(), "a(bc)");
+
+        let ast = RegexAst::star(RegexAst::concat(symbol('*'), symbol('+')));
+        assert_eq!(ast.infix().to_string(), "(\\*\\+)*");
+
+        let ast = RegexAst::concat(
+            RegexAst::class(vec![('a', 'z'), ('-', '-')], false),
+            RegexAst::star(RegexAst::class(vec![('0', '9')], true)),
+        );
+        assert_eq!(ast.infix().to_string(), "[a-z\\-][^0-9]*");
+        assert_eq!(ast.to_string(), "concat(class(a-z\\-),star(class(^0-9)))");
+
+        let ast = RegexAst::concat(RegexAst::star(RegexAst::Any), symbol('.'));
+        assert_eq!(ast.infix().to_string(), ".*\\.");
+        assert_eq!(ast.to_string(), "concat(star(any()),symbol(.))");
+    }
+
+    #[test]
+    fn check_repetition_printing() {
+        let ast = RegexAst::concat(
+            RegexAst::plus(symbol('a')),
+            RegexAst::optional(RegexAst::repeat(symbol('b'), 2, Some(3))),
+        );
+        assert_eq!(
+            ast.to_string(),
+            "concat(plus(symbol(a)),opt(repeat(symbol(b),2,3)))"
+        );
+        // a postfix `+` followed by an operand would be read as a union
+        assert_eq!(ast.infix().to_string(), "a+b{2,3}?");
+
+        let ast = RegexAst::union(
+            RegexAst::repeat(symbol('a'), 2, None),
+            RegexAst::repeat(symbol('b'), 4, Some(4)),
+        );
+        assert_eq!(
+            ast.to_string(),
+            "union(repeat(symbol(a),2,),repeat(symbol(b),4,4))"
+        );
+        assert_eq!(ast.infix().to_string(), "a{2,}|b{4}");
+    }
+
+    #[test]
+    fn check_boolean_operator_printing() {
+        let ast = RegexAst::intersection(
+            RegexAst::difference(symbol('a'), RegexAst::star(symbol('b'))),
+            RegexAst::complement(RegexAst::concat(symbol('c'), symbol('-'))),
+        );
+        assert_eq!(
+            ast.to_string(),
+            "inter(diff(symbol(a),star(symbol(b))),not(concat(symbol(c),symbol(-))))"
+        );
+        assert_eq!(ast.infix().to_string(), "a-b*&~(c\\-)");
+
+        let ast = RegexAst::concat(
+            RegexAst::complement(RegexAst::plus(symbol('a'))),
+            RegexAst::union(
+                symbol('b'),
+                RegexAst::intersection(symbol('c'), symbol('d')),
+            ),
+        );
+        assert_eq!(ast.infix().to_string(), "~a+(b|c&d)");
+    }
+
+    #[test]
+    fn check_symbols() {
+        let ast = RegexAst::union(
+            RegexAst::concat(symbol('a'), symbol('b')),
+            RegexAst::star(symbol('a')),
+        );
+        assert_eq!(ast.symbols(), HashSet::from(['a', 'b']));
+    }
+
+    #[test]
+    fn check_case_insensitive() {
+        let ast = RegexAst::concat(symbol('a'), RegexAst::star(symbol('B')));
+        assert_eq!(ast.case_insensitive().infix().to_string(), "(a|A)(B|b)*");
+
+        // characters without case, or with a multi character mapping, stay as they are
+        assert_eq!(symbol('1').case_insensitive(), symbol('1'));
+        assert_eq!(symbol('ß').case_insensitive(), symbol('ß'));
+
+        let ast = RegexAst::class(vec![('a', 'c'), ('x', 'x')], true);
+        assert_eq!(ast.case_insensitive().infix().to_string(), "[^a-cxA-CX]");
+    }
+
+    #[test]
+    fn check_size_limit() {
+        let ab = RegexAst::union(symbol('a'), symbol('b'));
+        for ast in [
+            RegexAst::Empty,
+            RegexAst::concat(
+                RegexAst::star(ab.clone()),
+                RegexAst::class(vec![('0', '9')], true),
+            ),
+            RegexAst::repeat(ab.clone(), 2, Some(5)),
+            RegexAst::repeat(RegexAst::Epsilon, 3, None),
+        ] {
+            let nfa_size = ast.to_nfa().num_states();
+            assert_eq!(ast.nfa_size(), nfa_size, "{}", ast);
+
+            assert!(ast.to_nfa_with_size_limit(nfa_size).is_some(), "{}", ast);
+            assert!(
+                ast.to_nfa_with_size_limit(nfa_size - 1).is_none(),
+                "{}",
+                ast
+            );
+        }
+
+        // rejected without building the copies
+        let ast = RegexAst::repeat(ab.clone(), 0, Some(u32::MAX));
+        assert!(ast.to_nfa_with_size_limit(10_000).is_none());
+
+        // the DFA of (a|b)*a(a|b){5} has 64 states, which is over the limit
+        // even though the NFAs are not
+        let last_but_five = RegexAst::concat(
+            RegexAst::star(ab.clone()),
+            RegexAst::concat(symbol('a'), RegexAst::repeat(ab, 5, Some(5))),
+        );
+        let ast = RegexAst::complement(last_but_five);
+        assert!(ast.nfa_size() <= 60);
+        assert!(ast.to_nfa_with_size_limit(60).is_none());
+        assert!(ast.to_nfa_with_size_limit(100).is_some());
+    }
+
+    #[test]
+    fn check_conversion_to_dfa() {
+        let ast = RegexAst::concat(
+            RegexAst::star(RegexAst::union(symbol('a'), symbol('b'))),
+            symbol('c'),
+        );
+        let dfa = ast.to_dfa();
+        assert!(dfa.run("abbac").is_ok_and(|res| res));
+        assert!(dfa.run("abcabc").is_ok_and(|res| !res));
+
+        let dfa = RegexAst::union(symbol('a'), RegexAst::Epsilon).to_dfa();
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| res));
+        assert!(dfa.run("aa").is_ok_and(|res| !res));
+
+        let dfa = RegexAst::union(symbol('a'), RegexAst::Empty).to_dfa();
+        assert!(dfa.run("").is_ok_and(|res| !res));
+        assert!(dfa.run("a").is_ok_and(|res| res));
+
+        let dfa = RegexAst::concat(symbol('a'), RegexAst::Empty).to_dfa();
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+
+        let dfa = RegexAst::repeat(symbol('a'), 2, Some(4)).to_dfa();
+        for (input, expected) in [("a", false), ("aa", true), ("aaaa", true), ("aaaaa", false)] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        let dfa = RegexAst::repeat(symbol('a'), 0, Some(0)).to_dfa();
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+
+        let dfa = RegexAst::plus(symbol('a')).to_dfa();
+        assert!(dfa.run("").is_ok_and(|res| !res));
+        assert!(dfa.run("aaa").is_ok_and(|res| res));
+
+        // strings of a and b with an even length, other than those of a only
+        let even = RegexAst::star(RegexAst::concat(RegexAst::Any, RegexAst::Any));
+        let ab = RegexAst::star(RegexAst::union(symbol('a'), symbol('b')));
+        let dfa = RegexAst::difference(
+            RegexAst::intersection(even, ab),
+            RegexAst::star(symbol('a')),
+        )
+        .to_dfa();
+        for (input, expected) in [
+            ("ab", true),
+            ("bbba", true),
+            ("aa", false),
+            ("abb", false),
+            ("ax", false),
+        ] {
+            assert_eq!(dfa.run(input).unwrap(), expected, "{}", input);
+        }
+
+        let dfa = RegexAst::complement(symbol('a')).to_dfa();
+        assert!(dfa.run("").is_ok_and(|res| res));
+        assert!(dfa.run("a").is_ok_and(|res| !res));
+        assert!(dfa.run("aa").is_ok_and(|res| res));
+        assert!(dfa.run("x").is_ok_and(|res| res));
+    }
+}
@@ -0,0 +1,364 @@
+//! This module contains the functions for searching a DFA's language inside a longer string
+//!
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Range,
+};
+
+use crate::{dfa::DFA, nfa::NFA, state::State, symbol_table::Symbol};
+
+/// A match of a DFA inside a string, given by byte offsets
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Match {
+    /// returns the byte range of the match
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// returns the matched part of the string the match was found in
+    pub fn as_str<'s>(&self, haystack: &'s str) -> &'s str {
+        &haystack[self.range()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Iterator over the non-overlapping leftmost-longest matches of a DFA in a string
+pub struct FindIter<'d, 's> {
+    dfa: &'d DFA,
+    haystack: &'s str,
+    // match_starts[i] is true if some match begins at byte offset i
+    match_starts: Vec<bool>,
+    // states from which a final state can be reached
+    live_states: Cow<'d, HashSet<State>>,
+    // only a match of the whole haystack is reported
+    anchored: bool,
+    // byte offset from which the next match is searched
+    position: usize,
+}
+
+impl Iterator for FindIter<'_, '_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        while self.position <= self.haystack.len() {
+            let start = self.position;
+            self.position = self.next_char_boundary(start);
+
+            if !self.match_starts[start] {
+                continue;
+            }
+
+            // a match starts here, unless the starts were not narrowed down by a reverse
+            // pass, so find its longest end
+            let end = match self.longest_match_end(start) {
+                Some(end) if !self.anchored || end == self.haystack.len() => end,
+                _ => continue,
+            };
+
+            if end > start {
+                // an empty match may still follow at `end`
+                self.position = end;
+            }
+
+            return Some(Match { start, end });
+        }
+
+        None
+    }
+}
+
+impl<'d, 's> FindIter<'d, 's> {
+    /// `match_starts` and `live_states` are the results of `accepting_suffix_starts`
+    /// and `live_states`, passed in so they can be computed once per DFA
+    pub(crate) fn new(
+        dfa: &'d DFA,
+        haystack: &'s str,
+        match_starts: Vec<bool>,
+        live_states: Cow<'d, HashSet<State>>,
+        anchored: bool,
+    ) -> FindIter<'d, 's> {
+        FindIter {
+            dfa,
+            haystack,
+            match_starts,
+            live_states,
+            anchored,
+            position: 0,
+        }
+    }
+}
+
+impl FindIter<'_, '_> {
+    /// returns the offset of the character boundary after `position`,
+    /// or one past the end once the end of the haystack is reached
+    fn next_char_boundary(&self, position: usize) -> usize {
+        match self.haystack[position..].chars().next() {
+            Some(ch) => position + ch.len_utf8(),
+            None => position + 1,
+        }
+    }
+
+    /// runs the DFA anchored at `start` and returns the end of the longest accepted prefix
+    fn longest_match_end(&self, start: usize) -> Option<usize> {
+        let mut current_state = self.dfa.start_state();
+        let mut end = None;
+
+        if self.dfa.final_states().contains(&current_state) {
+            end = Some(start);
+        }
+
+        for (offset, ch) in self.haystack[start..].char_indices() {
+            let symbol = self.dfa.symbol_table().lookup(ch);
+
+            match self.dfa.get_transition(&current_state, &symbol) {
+                Some(next_state) if self.live_states.contains(&next_state) => {
+                    current_state = next_state;
+                }
+                // no longer match is possible
+                _ => break,
+            }
+
+            if self.dfa.final_states().contains(&current_state) {
+                end = Some(start + offset + ch.len_utf8());
+            }
+        }
+
+        end
+    }
+}
+
+/// unanchored search
+impl DFA {
+    /// to check if some substring of `s` is accepted by the DFA
+    pub fn is_match_anywhere(&self, s: &str) -> bool {
+        self.earliest_match_end(s).is_some()
+    }
+
+    /// returns the DFA accepting the strings with a substring accepted by this DFA, the
+    /// automaton for Σ*LΣ*, `None` if the subset construction needs more than `state_limit` states
+    ///
+    /// it answers `is_match_anywhere` with a single run, so it is worth building once
+    /// for a DFA which is searched in many strings
+    pub fn try_substring_dfa(&self, state_limit: usize) -> Option<DFA> {
+        let symbols: Vec<Symbol> = self.symbol_table().input_symbols().collect();
+        let any_string = || NFA::from_symbols(&symbols, self.symbol_table()).kleene_star();
+
+        // every automaton is over the same symbol table, so concatenating them cannot fail
+        let nfa = any_string()
+            .concat(NFA::convert_dfa_to_nfa(self.clone()))
+            .and_then(|nfa| nfa.concat(any_string()))
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        DFA::try_convert_to_dfa(nfa, state_limit)
+    }
+
+    /// returns the leftmost-longest match in `s`
+    pub fn find(&self, s: &str) -> Option<Match> {
+        self.find_iter(s).next()
+    }
+
+    /// returns an iterator over the non-overlapping leftmost-longest matches in `s`
+    ///
+    /// the reversed automaton is run once backwards over `s` to mark the offsets at which
+    /// a match starts, each match then takes the leftmost remaining start and the longest
+    /// end found by running this DFA forwards from it
+    pub fn find_iter<'d, 's>(&'d self, s: &'s str) -> FindIter<'d, 's> {
+        FindIter::new(
+            self,
+            s,
+            self.reverse().accepting_suffix_starts(s),
+            Cow::Owned(self.live_states()),
+            false,
+        )
+    }
+
+    /// returns the end of the earliest ending match in `s`
+    ///
+    /// this runs the prefix closed automaton for Σ*L, whose states are sets of states of
+    /// this DFA, built lazily while scanning so the search needs a single pass over `s`
+    fn earliest_match_end(&self, s: &str) -> Option<usize> {
+        let mut active: HashSet<State> = HashSet::new();
+
+        for (position, ch) in s.char_indices() {
+            // a new run starts at every position
+            active.insert(self.start_state());
+
+            if active
+                .iter()
+                .any(|state| self.final_states().contains(state))
+            {
+                return Some(position);
+            }
+
+            let symbol = self.symbol_table().lookup(ch);
+            active = active
+                .iter()
+                .filter_map(|state| self.get_transition(state, &symbol))
+                .collect();
+        }
+
+        active.insert(self.start_state());
+
+        if active
+            .iter()
+            .any(|state| self.final_states().contains(state))
+        {
+            return Some(s.len());
+        }
+
+        None
+    }
+
+    /// used on the reversed DFA, marks every byte offset `i` of `s` for which some prefix
+    /// of `s[i..]` is accepted by the original DFA
+    pub(crate) fn accepting_suffix_starts(&self, s: &str) -> Vec<bool> {
+        let mut starts = vec![false; s.len() + 1];
+
+        // a run started at every offset j reads s[..j] backwards
+        let mut active: HashSet<State> = HashSet::new();
+        active.insert(self.start_state());
+        starts[s.len()] = self.final_states().contains(&self.start_state());
+
+        for (position, ch) in s.char_indices().rev() {
+            let symbol = self.symbol_table().lookup(ch);
+            active = active
+                .iter()
+                .filter_map(|state| self.get_transition(state, &symbol))
+                .collect();
+            active.insert(self.start_state());
+
+            starts[position] = active
+                .iter()
+                .any(|state| self.final_states().contains(state));
+        }
+
+        starts
+    }
+
+    /// returns the states from which some final state can be reached
+    pub(crate) fn live_states(&self) -> HashSet<State> {
+        let mut predecessors: HashMap<State, Vec<State>> = HashMap::new();
+        for state in self.begin_state_num()..=self.end_state_num() {
+            for symbol in self.symbol_table().symbols() {
+                if let Some(next_state) = self.get_transition(&state, symbol) {
+                    predecessors.entry(next_state).or_default().push(state);
+                }
+            }
+        }
+
+        let mut live: HashSet<State> = self.final_states().clone();
+        let mut q: VecDeque<State> = self.final_states().iter().copied().collect();
+
+        while let Some(state) = q.pop_front() {
+            for &previous_state in predecessors.get(&state).into_iter().flatten() {
+                if live.insert(previous_state) {
+                    q.push_back(previous_state);
+                }
+            }
+        }
+
+        live
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing;
+
+    fn matches(pattern: &str, haystack: &str) -> Vec<(usize, usize)> {
+        let dfa = parsing::create_dfa_from_reg_ex(pattern).unwrap();
+        dfa.find_iter(haystack)
+            .map(|found| (found.start, found.end))
+            .collect()
+    }
+
+    #[test]
+    fn check_match_anywhere() {
+        let dfa = parsing::create_dfa_from_reg_ex("concat(symbol(a),symbol(b))").unwrap();
+        assert!(dfa.is_match_anywhere("ab"));
+        assert!(dfa.is_match_anywhere("xxaby"));
+        assert!(dfa.is_match_anywhere("aaab"));
+        assert!(!dfa.is_match_anywhere("a b"));
+        assert!(!dfa.is_match_anywhere(""));
+
+        // star accepts the empty string, hence matches everywhere
+        let dfa = parsing::create_dfa_from_reg_ex("star(symbol(a))").unwrap();
+        assert!(dfa.is_match_anywhere("xyz"));
+        assert!(dfa.is_match_anywhere(""));
+    }
+
+    #[test]
+    fn check_substring_dfa() {
+        let dfa = parsing::create_dfa_from_reg_ex("concat(symbol(a),symbol(b))").unwrap();
+        let substring_dfa = dfa.try_substring_dfa(100).unwrap();
+
+        for haystack in ["ab", "xxaby", "aaab", "a b", "", "ba"] {
+            assert_eq!(
+                substring_dfa.run(haystack).unwrap(),
+                dfa.is_match_anywhere(haystack),
+                "{}",
+                haystack
+            );
+        }
+
+        // before a match of a(a|b){4}, the last five characters have to be remembered
+        let dfa = parsing::create_dfa_from_reg_ex(
+            "concat(symbol(a),repeat(union(symbol(a),symbol(b)),4,4))",
+        )
+        .unwrap();
+        assert!(dfa.try_substring_dfa(20).is_none());
+    }
+
+    #[test]
+    fn check_find_positions() {
+        let dfa = parsing::create_dfa_from_reg_ex("concat(symbol(a),symbol(b))").unwrap();
+        assert_eq!(dfa.find("xxaby"), Some(Match { start: 2, end: 4 }));
+        assert_eq!(dfa.find("aaab"), Some(Match { start: 2, end: 4 }));
+        assert_eq!(dfa.find("ab ab"), Some(Match { start: 0, end: 2 }));
+        assert_eq!(dfa.find("ba"), None);
+
+        // the leftmost start wins over an earlier end
+        let dfa = parsing::create_dfa_from_reg_ex(
+            "union(concat(symbol(a),concat(symbol(b),symbol(c))),symbol(b))",
+        )
+        .unwrap();
+        let found = dfa.find("xabc").unwrap();
+        assert_eq!(found.range(), 1..4);
+        assert_eq!(found.as_str("xabc"), "abc");
+
+        // byte offsets past multi-byte characters
+        let dfa = parsing::create_dfa_from_reg_ex("symbol(b)").unwrap();
+        assert_eq!(dfa.find("éb"), Some(Match { start: 2, end: 3 }));
+    }
+
+    #[test]
+    fn check_leftmost_longest_iteration() {
+        let pattern = "concat(symbol(a),star(symbol(a)))";
+        assert_eq!(matches(pattern, "aa baaa a"), vec![(0, 2), (4, 7), (8, 9)]);
+        assert_eq!(matches(pattern, "bbb"), vec![]);
+
+        // (ab)* is longest, not shortest
+        let pattern = "star(concat(symbol(a),symbol(b)))";
+        assert_eq!(matches(pattern, "abab"), vec![(0, 4), (4, 4)]);
+
+        // empty matches are reported between the non-empty ones
+        let pattern = "star(symbol(a))";
+        assert_eq!(matches(pattern, "baa"), vec![(0, 0), (1, 3), (3, 3)]);
+        assert_eq!(matches(pattern, ""), vec![(0, 0)]);
+    }
+}
@@ -0,0 +1,300 @@
+//! This module contains the conversion of a DFA back to a reg-ex by state elimination
+//!
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    dfa::DFA,
+    regex_ast::RegexAst,
+    state::State,
+    symbol_table::{next_char, previous_char},
+};
+
+/// edges of the automaton being reduced, from a node to a node, labelled by reg-exes
+type Edges = BTreeMap<(usize, usize), RegexAst>;
+
+impl DFA {
+    /// returns a reg-ex accepting the language of the DFA
+    ///
+    /// the useful states become nodes of an automaton whose edges are labelled by reg-exes,
+    /// with a new start node and a new final node, then the states are removed one by one,
+    /// the paths through a removed state becoming edges between its neighbours, until
+    /// a single edge from the start node to the final node is left
+    pub fn to_regex(&self) -> RegexAst {
+        let mut useful: Vec<State> = self.useful_states().into_iter().collect();
+        useful.sort();
+
+        if !useful.contains(&self.start_state()) {
+            return RegexAst::Empty;
+        }
+
+        let node: HashMap<State, usize> = useful
+            .iter()
+            .enumerate()
+            .map(|(index, &state)| (state, index))
+            .collect();
+        let (start_node, final_node) = (useful.len(), useful.len() + 1);
+
+        let mut edges: Edges = BTreeMap::new();
+        edges.insert((start_node, node[&self.start_state()]), RegexAst::Epsilon);
+
+        for &state in useful.iter() {
+            if self.final_states().contains(&state) {
+                edges.insert((node[&state], final_node), RegexAst::Epsilon);
+            }
+
+            // the characters moving to each state, in increasing order
+            let mut chars_to: BTreeMap<usize, Vec<(char, char)>> = BTreeMap::new();
+            for (start, end, symbol) in self.symbol_table().segments() {
+                if let Some(next_state) = self.get_transition(&state, &symbol) {
+                    if let Some(&next_node) = node.get(&next_state) {
+                        chars_to.entry(next_node).or_default().push((start, end));
+                    }
+                }
+            }
+
+            for (next_node, ranges) in chars_to {
+                edges.insert((node[&state], next_node), char_set(ranges));
+            }
+        }
+
+        for _ in 0..useful.len() {
+            let removed = cheapest_node(&edges, start_node, final_node);
+            eliminate(&mut edges, removed);
+        }
+
+        edges
+            .remove(&(start_node, final_node))
+            .unwrap_or(RegexAst::Empty)
+    }
+}
+
+/// returns the node to remove next, the one creating the fewest new paths
+fn cheapest_node(edges: &Edges, start_node: usize, final_node: usize) -> usize {
+    let mut degrees: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+    for &(from, to) in edges.keys() {
+        if from == to {
+            continue;
+        }
+        degrees.entry(from).or_default().1 += 1;
+        degrees.entry(to).or_default().0 += 1;
+    }
+
+    degrees
+        .into_iter()
+        .filter(|&(node, _)| node != start_node && node != final_node)
+        .min_by_key(|&(node, (in_degree, out_degree))| (in_degree * out_degree, node))
+        .map(|(node, _)| node)
+        .unwrap_or_else(|| panic!("no node left to eliminate"))
+}
+
+/// removes a node, replacing every path through it by an edge
+fn eliminate(edges: &mut Edges, removed: usize) {
+    let self_loop = edges.remove(&(removed, removed)).map(star);
+
+    let incoming: Vec<(usize, RegexAst)> = edges
+        .iter()
+        .filter(|&(&(_, to), _)| to == removed)
+        .map(|(&(from, _), label)| (from, label.clone()))
+        .collect();
+    let outgoing: Vec<(usize, RegexAst)> = edges
+        .iter()
+        .filter(|&(&(from, _), _)| from == removed)
+        .map(|(&(_, to), label)| (to, label.clone()))
+        .collect();
+    edges.retain(|&(from, to), _| from != removed && to != removed);
+
+    for (from, first) in incoming.iter() {
+        for (to, last) in outgoing.iter() {
+            let path = match &self_loop {
+                Some(self_loop) => concat(concat(first.clone(), self_loop.clone()), last.clone()),
+                None => concat(first.clone(), last.clone()),
+            };
+
+            let label = match edges.remove(&(*from, *to)) {
+                Some(existing) => union(existing, path),
+                None => path,
+            };
+            edges.insert((*from, *to), label);
+        }
+    }
+}
+
+/// returns the reg-ex matching the characters of the ranges, which are in increasing order
+fn char_set(ranges: Vec<(char, char)>) -> RegexAst {
+    let ranges = merge_adjacent(ranges);
+
+    // the characters outside of the ranges
+    let mut complement = Vec::new();
+    let mut next = Some('\0');
+    for &(lo, hi) in ranges.iter() {
+        if let Some(start) = next {
+            if start < lo {
+                complement.push((start, previous_char(lo)));
+            }
+        }
+        next = next_char(hi);
+    }
+    if let Some(start) = next {
+        complement.push((start, char::MAX));
+    }
+
+    match (ranges.as_slice(), complement.as_slice()) {
+        (_, []) => RegexAst::Any,
+        (&[(lo, hi)], _) if lo == hi => RegexAst::Symbol(lo),
+        _ if complement.len() < ranges.len() => RegexAst::class(complement, true),
+        _ => RegexAst::class(ranges, false),
+    }
+}
+
+/// joins the ranges which follow each other without a gap, apart from the surrogates
+fn merge_adjacent(ranges: Vec<(char, char)>) -> Vec<(char, char)> {
+    let mut merged: Vec<(char, char)> = Vec::new();
+
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some((_, last)) if next_char(*last) == Some(lo) => *last = hi,
+            _ => merged.push((lo, hi)),
+        }
+    }
+
+    merged
+}
+
+// the constructors below simplify the reg-ex as it is built, so that it stays readable
+
+/// returns whether the reg-ex matches the empty string
+fn is_nullable(ast: &RegexAst) -> bool {
+    match ast {
+        RegexAst::Epsilon | RegexAst::Star(_) => true,
+        RegexAst::Repeat { min: 0, .. } => true,
+        RegexAst::Union(first, second) => is_nullable(first) || is_nullable(second),
+        RegexAst::Concat(first, second) => is_nullable(first) && is_nullable(second),
+        _ => false,
+    }
+}
+
+fn union(first: RegexAst, second: RegexAst) -> RegexAst {
+    match (first, second) {
+        (first, second) if first == second => first,
+        (RegexAst::Empty, other) | (other, RegexAst::Empty) => other,
+        (RegexAst::Epsilon, other) | (other, RegexAst::Epsilon) => {
+            if is_nullable(&other) {
+                other
+            } else if let RegexAst::Repeat {
+                inner,
+                min: 1,
+                max: None,
+            } = other
+            {
+                star(*inner)
+            } else {
+                RegexAst::optional(other)
+            }
+        }
+        (first, second) => RegexAst::union(first, second),
+    }
+}
+
+fn concat(first: RegexAst, second: RegexAst) -> RegexAst {
+    match (first, second) {
+        (RegexAst::Empty, _) | (_, RegexAst::Empty) => RegexAst::Empty,
+        (RegexAst::Epsilon, other) | (other, RegexAst::Epsilon) => other,
+        // R R* and R* R are R+
+        (first, RegexAst::Star(inner)) if first == *inner => RegexAst::plus(first),
+        (RegexAst::Star(inner), second) if second == *inner => RegexAst::plus(second),
+        (first, second) => RegexAst::concat(first, second),
+    }
+}
+
+fn star(inner: RegexAst) -> RegexAst {
+    match inner {
+        RegexAst::Empty | RegexAst::Epsilon => RegexAst::Epsilon,
+        RegexAst::Star(_) => inner,
+        // (R+)* and (R?)* are R*
+        RegexAst::Repeat {
+            inner,
+            min: 0 | 1,
+            max: None | Some(1),
+        } => RegexAst::star(*inner),
+        inner => RegexAst::star(inner),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{infix_parsing::parse_infix_reg_ex, parsing::create_dfa_from_reg_ex};
+
+    use super::*;
+
+    /// the reg-ex of the DFA of `reg_ex`, checked to accept the same language
+    fn round_trip(reg_ex: &str) -> RegexAst {
+        let dfa = create_dfa_from_reg_ex(reg_ex).unwrap();
+        let ast = dfa.to_regex();
+
+        assert_eq!(
+            dfa.equivalent(&ast.to_dfa()),
+            Ok(()),
+            "{} became {}",
+            reg_ex,
+            ast
+        );
+
+        // and so does the printed reg-ex once parsed again
+        let reparsed = parse_infix_reg_ex(&ast.infix().to_string()).unwrap();
+        assert_eq!(
+            dfa.equivalent(&reparsed.to_dfa()),
+            Ok(()),
+            "{}",
+            ast.infix()
+        );
+
+        ast
+    }
+
+    #[test]
+    fn check_conversion_to_regex() {
+        for reg_ex in [
+            "empty()",
+            "eps()",
+            "symbol(a)",
+            "concat(symbol(a),star(symbol(b)))",
+            "star(union(symbol(a),concat(symbol(b),symbol(c))))",
+            "concat(star(union(symbol(a),symbol(b))),concat(symbol(a),union(symbol(a),symbol(b))))",
+            "repeat(class(0-9),2,4)",
+            "inter(star(concat(symbol(a),symbol(a))),star(concat(symbol(a),concat(symbol(a),symbol(a)))))",
+            "diff(plus(class(a-z)),union(concat(symbol(i),symbol(f)),concat(symbol(d),symbol(o))))",
+            "not(concat(star(any()),concat(symbol(\\\\),symbol(n))))",
+            "concat(class(^a-c),opt(symbol(-)))",
+            "star(any())",
+        ] {
+            round_trip(reg_ex);
+        }
+    }
+
+    #[test]
+    fn check_simplification() {
+        for (reg_ex, expected) in [
+            ("empty()", "[]"),
+            ("eps()", "()"),
+            ("concat(symbol(a),star(symbol(b)))", "ab*"),
+            ("plus(symbol(a))", "a+"),
+            ("union(symbol(a),union(symbol(b),symbol(c)))", "[a-c]"),
+            ("union(eps(),concat(symbol(a),symbol(b)))", "(ab)?"),
+            ("class(^x)", "[^x]"),
+            ("star(any())", ".*"),
+        ] {
+            assert_eq!(
+                round_trip(reg_ex).infix().to_string(),
+                expected,
+                "{}",
+                reg_ex
+            );
+        }
+
+        assert_eq!(
+            round_trip("concat(symbol(a),star(symbol(b)))").to_string(),
+            "concat(symbol(a),star(symbol(b)))"
+        );
+    }
+}
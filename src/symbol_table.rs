@@ -1,25 +1,51 @@
 //! This module contains the necessary functions for Symbol and SymbolTable
 
 use std::{
-    collections::{hash_map::Keys, HashMap},
+    collections::{hash_map::Keys, BTreeMap, BTreeSet, HashMap},
     ops::Index,
 };
 
 /// special value for EPSILON
 const EPSILON_VALUE: usize = 0;
 
+/// special value for OTHER
+const OTHER_VALUE: usize = 1;
+
 /// Type for Symbols
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum Symbol {
     Epsilon,
     Character(char),
+    /// every character from the first up to the second one, both included
+    Range(char, char),
+    /// stands for every character which is not in the symbol table
+    Other,
+}
+
+/// the surrogate code points, which are not characters
+const SURROGATES: (u32, u32) = (0xD800, 0xDFFF);
+
+impl Symbol {
+    /// a range of a single character is written as that character
+    fn from_range(lo: char, hi: char) -> Symbol {
+        if lo == hi {
+            Symbol::Character(lo)
+        } else {
+            Symbol::Range(lo, hi)
+        }
+    }
 }
 
 /// Struct to hold the symbols and their corresponding numbers
+///
+/// the characters and ranges of the table never overlap, adding a range which overlaps
+/// existing ones splits them, so that every range added so far is a union of symbols
 #[derive(Debug, Clone, PartialEq)]
 pub struct SymbolTable {
     symbol_to_number: HashMap<Symbol, usize>,
     number_to_symbol: HashMap<usize, Symbol>,
+    // first character of every character or range symbol -> its last character
+    ranges: BTreeMap<char, char>,
     current_number: usize,
 }
 
@@ -28,7 +54,7 @@ impl Index<Symbol> for SymbolTable {
 
     /// returns the number of corresponding symbol when indexed with Symbol
     fn index(&self, index: Symbol) -> &Self::Output {
-        return &self.symbol_to_number[&index];
+        &self.symbol_to_number[&index]
     }
 }
 
@@ -37,54 +63,134 @@ impl Index<usize> for SymbolTable {
 
     /// returns the Symbol for the corresponding number, when indexed with usize
     fn index(&self, index: usize) -> &Self::Output {
-        return &self.number_to_symbol[&index];
+        &self.number_to_symbol[&index]
+    }
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        SymbolTable::new()
     }
 }
 
 impl SymbolTable {
-    /// returns a new instance of symbol table initialised with Symbol::Epsilon
+    /// returns a new instance of symbol table initialised with Symbol::Epsilon and Symbol::Other
     pub fn new() -> SymbolTable {
         let mut symbol_table = SymbolTable {
             symbol_to_number: HashMap::new(),
             number_to_symbol: HashMap::new(),
-            // 0 is reserved for EPSILON
-            current_number: EPSILON_VALUE + 1,
+            ranges: BTreeMap::new(),
+            // 0 is reserved for EPSILON and 1 for OTHER
+            current_number: OTHER_VALUE + 1,
         };
 
-        symbol_table
-            .symbol_to_number
-            .insert(Symbol::Epsilon, EPSILON_VALUE);
-
-        symbol_table
-            .number_to_symbol
-            .insert(EPSILON_VALUE, Symbol::Epsilon);
+        for (symbol, number) in [
+            (Symbol::Epsilon, EPSILON_VALUE),
+            (Symbol::Other, OTHER_VALUE),
+        ] {
+            symbol_table.symbol_to_number.insert(symbol, number);
+            symbol_table.number_to_symbol.insert(number, symbol);
+        }
 
         symbol_table
     }
 
     /// add_symbol is for adding symbol
     pub fn add_symbol(&mut self, symbol: Symbol) {
-        if symbol == Symbol::Epsilon {
-            return;
+        match symbol {
+            Symbol::Epsilon | Symbol::Other => {}
+            Symbol::Character(ch) => self.add_range(ch, ch),
+            Symbol::Range(lo, hi) => self.add_range(lo, hi),
         }
+    }
+
+    /// add character is for inserting characters other than EPSILON
+    pub fn add_character(&mut self, ch: char) {
+        self.add_range(ch, ch);
+    }
 
-        if self.symbol_to_number.contains_key(&symbol) {
+    /// adds the characters from `lo` up to `hi`, splitting the symbols it overlaps
+    pub fn add_range(&mut self, lo: char, hi: char) {
+        if lo > hi || self.symbols_in_range(lo, hi).is_some() {
             return;
         }
 
-        // start inserting with 1
-        self.symbol_to_number.insert(symbol, self.current_number);
-        self.number_to_symbol.insert(self.current_number, symbol);
+        // the new symbols start at the first character of every range and
+        // right after its last character, where covered by some range
+        let mut intervals: Vec<(u32, u32)> = self
+            .ranges
+            .iter()
+            .map(|(&lo, &hi)| (lo as u32, hi as u32))
+            .collect();
+        intervals.push((lo as u32, hi as u32));
 
-        self.current_number += 1;
+        let boundaries: BTreeSet<u32> = intervals
+            .iter()
+            .flat_map(|&(lo, hi)| [lo, hi + 1])
+            .collect();
+        let boundaries: Vec<u32> = boundaries.into_iter().collect();
+
+        let mut new_ranges = BTreeMap::new();
+        for window in boundaries.windows(2) {
+            let (start, end) = (window[0], window[1] - 1);
+
+            if !intervals.iter().any(|&(lo, hi)| lo <= start && start <= hi) {
+                continue;
+            }
+            if let Some((start, end)) = char_range(start, end) {
+                new_ranges.insert(start, end);
+            }
+        }
+
+        for (&lo, &hi) in self.ranges.iter() {
+            if new_ranges.get(&lo) != Some(&hi) {
+                let number = self.symbol_to_number[&Symbol::from_range(lo, hi)];
+                self.symbol_to_number.remove(&Symbol::from_range(lo, hi));
+                self.number_to_symbol.remove(&number);
+            }
+        }
+
+        for (&lo, &hi) in new_ranges.iter() {
+            let symbol = Symbol::from_range(lo, hi);
+            if self.symbol_to_number.contains_key(&symbol) {
+                continue;
+            }
+
+            self.symbol_to_number.insert(symbol, self.current_number);
+            self.number_to_symbol.insert(self.current_number, symbol);
+
+            self.current_number += 1;
+        }
+
+        self.ranges = new_ranges;
     }
 
-    /// add character is for inserting characters other than EPSILON
-    pub fn add_character(&mut self, ch: char) {
-        self.add_symbol(Symbol::Character(ch));
+    /// returns the symbols making up the characters from `lo` up to `hi`,
+    /// `None` unless the range was added to the table
+    pub fn symbols_in_range(&self, lo: char, hi: char) -> Option<Vec<Symbol>> {
+        let mut symbols = Vec::new();
+        let mut expected = Some(lo);
+
+        for (&start, &end) in self.ranges.range(lo..=hi) {
+            // consecutive symbols must leave no gap, apart from the surrogates
+            if expected != Some(start) {
+                return None;
+            }
+
+            symbols.push(Symbol::from_range(start, end));
+            expected = next_char(end);
+        }
+
+        if symbols.is_empty() || expected != next_char(hi) {
+            return None;
+        }
+
+        Some(symbols)
     }
 
     #[allow(dead_code)]
+    // the table always contains epsilon and other, so it has no is_empty
+    #[allow(clippy::len_without_is_empty)]
     /// returns the number of symbols present
     pub fn len(&self) -> usize {
         self.symbol_to_number.len()
@@ -93,6 +199,210 @@ impl SymbolTable {
     pub fn symbols(&self) -> Keys<'_, Symbol, usize> {
         self.symbol_to_number.keys()
     }
+
+    /// returns the symbols an input character can move on, every symbol except epsilon,
+    /// together they cover every character since Symbol::Other stands for the rest
+    pub fn input_symbols(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.symbols()
+            .copied()
+            .filter(|&symbol| symbol != Symbol::Epsilon)
+    }
+
+    /// returns a table whose symbols split both tables, so that every symbol of
+    /// either table is a union of symbols of the merged one
+    pub fn merge(&self, other: &SymbolTable) -> SymbolTable {
+        let mut symbol_table = self.clone();
+
+        for (&lo, &hi) in other.ranges.iter() {
+            symbol_table.add_range(lo, hi);
+        }
+
+        symbol_table
+    }
+
+    /// returns the symbol of this table containing `symbol`,
+    /// `None` if the characters of `symbol` belong to more than one symbol of the table
+    pub fn containing_symbol(&self, symbol: Symbol) -> Option<Symbol> {
+        match symbol {
+            Symbol::Epsilon | Symbol::Other => Some(symbol),
+            Symbol::Character(ch) => Some(self.lookup(ch)),
+            Symbol::Range(lo, hi) => {
+                let containing = self.lookup(lo);
+                let is_contained = match containing {
+                    Symbol::Range(_, end) => hi <= end,
+                    // no symbol of the table may start inside the range
+                    Symbol::Other => self.ranges.range(lo..=hi).next().is_none(),
+                    Symbol::Epsilon | Symbol::Character(_) => false,
+                };
+
+                is_contained.then_some(containing)
+            }
+        }
+    }
+
+    /// maps every symbol of `finer` to the symbol of this table containing it,
+    /// `None` unless every symbol of `finer` lies inside a single symbol of this table
+    pub fn refinement(&self, finer: &SymbolTable) -> Option<HashMap<Symbol, Symbol>> {
+        // the other characters of `finer` must not include characters of this table
+        for (&lo, &hi) in self.ranges.iter() {
+            finer.symbols_in_range(lo, hi)?;
+        }
+
+        finer
+            .symbols()
+            .map(|&symbol| Some((symbol, self.containing_symbol(symbol)?)))
+            .collect()
+    }
+
+    /// returns the number of characters moving on `symbol`, 0 for epsilon
+    pub fn num_chars(&self, symbol: Symbol) -> u32 {
+        match symbol {
+            Symbol::Epsilon => 0,
+            Symbol::Character(_) => 1,
+            Symbol::Range(lo, hi) => range_len(lo, hi),
+            Symbol::Other => {
+                let in_table: u32 = self.ranges.iter().map(|(&lo, &hi)| range_len(lo, hi)).sum();
+
+                range_len('\0', char::MAX) - in_table
+            }
+        }
+    }
+
+    /// returns the `n`-th character moving on `symbol`, counting from 0 in increasing order
+    pub fn nth_char(&self, symbol: Symbol, mut n: u32) -> Option<char> {
+        for (start, end, segment_symbol) in self.segments() {
+            if segment_symbol != symbol {
+                continue;
+            }
+
+            let len = range_len(start, end);
+            if n >= len {
+                n -= len;
+                continue;
+            }
+
+            let mut code = start as u32 + n;
+            if (start as u32) < SURROGATES.0 && code >= SURROGATES.0 {
+                code += SURROGATES.1 - SURROGATES.0 + 1;
+            }
+
+            return char::from_u32(code);
+        }
+
+        None
+    }
+
+    /// returns a character moving on `symbol`, preferring letters, digits and then
+    /// printable characters for Symbol::Other, `None` for epsilon or if every character
+    /// is in the table
+    pub fn representative(&self, symbol: Symbol) -> Option<char> {
+        match symbol {
+            Symbol::Epsilon => None,
+            Symbol::Character(ch) | Symbol::Range(ch, _) => Some(ch),
+            Symbol::Other => ('a'..='z')
+                .chain('A'..='Z')
+                .chain('0'..='9')
+                .find(|&ch| self.lookup(ch) == Symbol::Other)
+                .or_else(|| {
+                    let others: Vec<(char, char)> = self
+                        .segments()
+                        .into_iter()
+                        .filter(|&(_, _, symbol)| symbol == Symbol::Other)
+                        .map(|(lo, hi, _)| (lo, hi))
+                        .collect();
+
+                    // the control characters only if nothing else is left
+                    others
+                        .iter()
+                        .find(|&&(_, hi)| hi >= ' ')
+                        .map(|&(lo, _)| lo.max(' '))
+                        .or_else(|| others.first().map(|&(lo, _)| lo))
+                }),
+        }
+    }
+
+    /// returns the ranges of characters moving on each symbol, ordered by their
+    /// first character, the gaps between the symbols of the table move on Symbol::Other,
+    /// like the ranges of the table a segment may span the surrogates
+    pub fn segments(&self) -> Vec<(char, char, Symbol)> {
+        let mut segments = Vec::new();
+        let mut next = 0;
+
+        for (&lo, &hi) in self.ranges.iter() {
+            if next < lo as u32 {
+                if let Some((start, end)) = char_range(next, lo as u32 - 1) {
+                    segments.push((start, end, Symbol::Other));
+                }
+            }
+
+            segments.push((lo, hi, Symbol::from_range(lo, hi)));
+            next = hi as u32 + 1;
+        }
+
+        if let Some((start, end)) = char_range(next, char::MAX as u32) {
+            segments.push((start, end, Symbol::Other));
+        }
+
+        segments
+    }
+
+    /// returns the symbol an input character moves on, Symbol::Other if it is not in the table
+    pub fn lookup(&self, ch: char) -> Symbol {
+        match self.ranges.range(..=ch).next_back() {
+            Some((&lo, &hi)) if ch <= hi => Symbol::from_range(lo, hi),
+            _ => Symbol::Other,
+        }
+    }
+}
+
+/// returns the character after `ch`, skipping the surrogates
+pub(crate) fn next_char(ch: char) -> Option<char> {
+    match ch {
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => char::from_u32(ch as u32 + 1),
+    }
+}
+
+/// returns the character before `ch`, which must not be the first one, skipping the surrogates
+pub(crate) fn previous_char(ch: char) -> char {
+    match ch {
+        '\u{E000}' => '\u{D7FF}',
+        _ => {
+            char::from_u32(ch as u32 - 1).unwrap_or_else(|| panic!("no character before {:?}", ch))
+        }
+    }
+}
+
+/// returns the number of characters from `lo` up to `hi`, which are not surrogates
+fn range_len(lo: char, hi: char) -> u32 {
+    let (lo, hi) = (lo as u32, hi as u32);
+    let surrogates = if lo < SURROGATES.0 && SURROGATES.1 < hi {
+        SURROGATES.1 - SURROGATES.0 + 1
+    } else {
+        0
+    };
+
+    hi - lo + 1 - surrogates
+}
+
+/// converts a range of code points to characters, leaving out the surrogates
+fn char_range(start: u32, end: u32) -> Option<(char, char)> {
+    let start = if (SURROGATES.0..=SURROGATES.1).contains(&start) {
+        SURROGATES.1 + 1
+    } else {
+        start
+    };
+    let end = if (SURROGATES.0..=SURROGATES.1).contains(&end) {
+        SURROGATES.0 - 1
+    } else {
+        end
+    };
+
+    if start > end {
+        return None;
+    }
+
+    Some((char::from_u32(start)?, char::from_u32(end)?))
 }
 
 #[cfg(test)]
@@ -112,9 +422,194 @@ mod tests {
         st.add_character('c');
 
         assert!(st.symbol_to_number.contains_key(&Symbol::Character('c')));
-        assert_eq!(st.symbol_to_number[&Symbol::Character('c')], 1);
-        assert_eq!(st.number_to_symbol[&1], Symbol::Character('c'));
+        assert_eq!(st.symbol_to_number[&Symbol::Character('c')], 2);
+        assert_eq!(st.number_to_symbol[&2], Symbol::Character('c'));
+
+        // epsilon, other and c
+        assert_eq!(st.len(), 3);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut st = SymbolTable::new();
+        st.add_character('c');
+
+        assert_eq!(st.lookup('c'), Symbol::Character('c'));
+        assert_eq!(st.lookup('d'), Symbol::Other);
+        assert_eq!(st.lookup('é'), Symbol::Other);
+
+        let mut input_symbols: Vec<Symbol> = st.input_symbols().collect();
+        input_symbols.sort_by_key(|symbol| st[*symbol]);
+        assert_eq!(input_symbols, vec![Symbol::Other, Symbol::Character('c')]);
+    }
+
+    #[test]
+    fn test_adding_overlapping_ranges() {
+        let mut st = SymbolTable::new();
+        st.add_range('a', 'z');
+        st.add_character('m');
+        st.add_range('0', '9');
+        st.add_range('5', 'c');
+
+        assert_eq!(st.lookup('a'), Symbol::Range('a', 'c'));
+        assert_eq!(st.lookup('m'), Symbol::Character('m'));
+        assert_eq!(st.lookup('q'), Symbol::Range('n', 'z'));
+        assert_eq!(st.lookup('7'), Symbol::Range('5', '9'));
+        assert_eq!(st.lookup('@'), Symbol::Range(':', '`'));
+        assert_eq!(st.lookup('/'), Symbol::Other);
+        assert_eq!(st.lookup('{'), Symbol::Other);
 
-        assert_eq!(st.len(), 2);
+        assert_eq!(
+            st.symbols_in_range('a', 'z'),
+            Some(vec![
+                Symbol::Range('a', 'c'),
+                Symbol::Range('d', 'l'),
+                Symbol::Character('m'),
+                Symbol::Range('n', 'z'),
+            ])
+        );
+        assert_eq!(st.symbols_in_range('a', 'y'), None);
+
+        // epsilon, other, 0-4, 5-9, :-`, a-c, d-l, m and n-z
+        assert_eq!(st.len(), 9);
+    }
+
+    #[test]
+    fn test_merging_symbol_tables() {
+        let mut first = SymbolTable::new();
+        first.add_range('a', 'm');
+        let mut second = SymbolTable::new();
+        second.add_range('k', 'z');
+        second.add_character('0');
+
+        let merged = first.merge(&second);
+        assert_eq!(
+            merged.symbols_in_range('a', 'z'),
+            Some(vec![
+                Symbol::Range('a', 'j'),
+                Symbol::Range('k', 'm'),
+                Symbol::Range('n', 'z')
+            ])
+        );
+        assert_eq!(merged.lookup('0'), Symbol::Character('0'));
+
+        // every merged symbol lies inside a single symbol of either table
+        assert_eq!(
+            first.containing_symbol(Symbol::Range('k', 'm')),
+            Some(Symbol::Range('a', 'm'))
+        );
+        assert_eq!(
+            second.containing_symbol(Symbol::Range('a', 'j')),
+            Some(Symbol::Other)
+        );
+        assert_eq!(first.containing_symbol(Symbol::Other), Some(Symbol::Other));
+        assert!(first.refinement(&merged).is_some());
+        assert!(second.refinement(&merged).is_some());
+
+        // but not the other way round
+        assert_eq!(merged.containing_symbol(Symbol::Range('a', 'm')), None);
+        assert_eq!(second.containing_symbol(Symbol::Range('a', 'z')), None);
+        assert!(merged.refinement(&first).is_none());
+    }
+
+    #[test]
+    fn test_representatives() {
+        let mut st = SymbolTable::new();
+        st.add_range('a', 'c');
+        st.add_character('b');
+
+        assert_eq!(st.representative(Symbol::Character('a')), Some('a'));
+        assert_eq!(st.representative(Symbol::Character('b')), Some('b'));
+        assert_eq!(st.representative(Symbol::Epsilon), None);
+        assert_eq!(st.representative(Symbol::Other), Some('d'));
+
+        // only the control characters are left outside of the table
+        st.add_range(' ', char::MAX);
+        assert_eq!(st.representative(Symbol::Other), Some('\0'));
+
+        st.add_range('\0', char::MAX);
+        assert_eq!(st.representative(Symbol::Other), None);
+    }
+
+    #[test]
+    fn test_segments() {
+        let mut st = SymbolTable::new();
+        assert_eq!(st.segments(), vec![('\0', char::MAX, Symbol::Other)]);
+
+        st.add_character('\0');
+        st.add_range('b', 'c');
+        assert_eq!(
+            st.segments(),
+            vec![
+                ('\0', '\0', Symbol::Character('\0')),
+                ('\u{1}', 'a', Symbol::Other),
+                ('b', 'c', Symbol::Range('b', 'c')),
+                ('d', char::MAX, Symbol::Other)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nth_character() {
+        let mut st = SymbolTable::new();
+        st.add_range('b', 'd');
+        st.add_range('\u{D7FE}', '\u{E001}');
+
+        assert_eq!(st.nth_char(Symbol::Range('b', 'd'), 0), Some('b'));
+        assert_eq!(st.nth_char(Symbol::Range('b', 'd'), 2), Some('d'));
+        assert_eq!(st.nth_char(Symbol::Range('b', 'd'), 3), None);
+
+        // the surrogates are skipped
+        let range = Symbol::Range('\u{D7FE}', '\u{E001}');
+        assert_eq!(st.nth_char(range, 2), Some('\u{E000}'));
+
+        // the other characters continue after the gap before b
+        assert_eq!(st.nth_char(Symbol::Other, 0), Some('\0'));
+        assert_eq!(st.nth_char(Symbol::Other, 'b' as u32), Some('e'));
+        let last = st.num_chars(Symbol::Other) - 1;
+        assert_eq!(st.nth_char(Symbol::Other, last), Some(char::MAX));
+        assert_eq!(st.nth_char(Symbol::Other, last + 1), None);
+    }
+
+    #[test]
+    fn test_number_of_characters() {
+        let mut st = SymbolTable::new();
+        assert_eq!(st.num_chars(Symbol::Other), 0x110000 - 0x800);
+
+        st.add_range('a', 'z');
+        st.add_range('\u{D000}', '\u{E000}');
+        st.add_character('é');
+
+        assert_eq!(st.num_chars(Symbol::Epsilon), 0);
+        assert_eq!(st.num_chars(Symbol::Character('é')), 1);
+        assert_eq!(st.num_chars(Symbol::Range('a', 'z')), 26);
+        assert_eq!(
+            st.num_chars(Symbol::Range('\u{D000}', '\u{E000}')),
+            0x1001 - 0x800
+        );
+        assert_eq!(
+            st.num_chars(Symbol::Other),
+            0x110000 - 0x800 - 27 - (0x1001 - 0x800)
+        );
+    }
+
+    #[test]
+    fn test_ranges_around_surrogates() {
+        let mut st = SymbolTable::new();
+        st.add_range('\u{D000}', '\u{F000}');
+        st.add_range('\u{D7FF}', '\u{E000}');
+
+        assert_eq!(st.lookup('\u{D7FF}'), Symbol::Range('\u{D7FF}', '\u{E000}'));
+        assert_eq!(st.lookup('\u{E001}'), Symbol::Range('\u{E001}', '\u{F000}'));
+        assert!(st.symbols_in_range('\u{D000}', '\u{F000}').is_some());
+    }
+
+    #[test]
+    fn test_next_and_previous_char() {
+        assert_eq!(next_char('a'), Some('b'));
+        assert_eq!(next_char('\u{D7FF}'), Some('\u{E000}'));
+        assert_eq!(next_char(char::MAX), None);
+        assert_eq!(previous_char('b'), 'a');
+        assert_eq!(previous_char('\u{E000}'), '\u{D7FF}');
     }
 }